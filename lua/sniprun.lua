@@ -28,9 +28,18 @@ M.config_values = {
         -- "Terminal",
         -- "TerminalWithCode",
         -- "Api",
-        -- "NvimNotify"
+        -- "NvimNotify",
+        -- "InsertBelow",
+        -- "QuickfixErr",
+        -- "LoclistErr",
+        -- "Diff"
     },
 
+    display_rules = {}, -- per filetype/interpreter overrides of 'display', first matching rule is used
+    -- ex: { filetype = "python", display = { "VirtualText" } },
+    --     { interpreter = "C_original", status = "err", display = { "Terminal" } },
+    --     { min_lines = 10, display = { "TempFloatingWindow" } },
+
     live_display = { "VirtualTextOk" }, -- displayed only for live mode
 
     display_options = {
@@ -41,7 +50,18 @@ M.config_values = {
         terminal_width = 45,                -- change the terminal display option width (if vertical)
         terminal_height = 20,               -- change the terminal display option heigth (if horizontal)
         notification_timeout = 5,           -- timeout for nvim_notify output
-        notification_render = "default"     -- nvim_notify style
+        notification_render = "default",    -- nvim_notify style
+        image_viewer = "",                  -- command to open captured images with (ex: "xdg-open")
+        table_column_width = 30,            -- max width of the columns of tabular results
+        table_scratch_buffer = false,       -- also open tabular results as csv in a scratch buffer
+        output_max_lines = 5000,            -- longer outputs are truncated (0: no limit),
+        output_max_bytes = 1000000,         -- the full output can be opened with :SnipShow
+        virtual_text = {                    -- how VirtualText summarizes results on one line
+            prefix = "<- ",
+            ok_line = "last",               -- line shown: "first", "last" or a regex (first matching line)
+            err_line = "first",             -- same, for errors
+            max_width = 0,                  -- longer lines are cut with "…" (0: no limit)
+        },
     },
 
     show_no_output = {
@@ -49,6 +69,11 @@ M.config_values = {
         "TempFloatingWindow", -- implies LongTempFloatingWindow, which is not a correct key here
     },
 
+    history = {
+        enabled = true,     -- record the runs (code and results) in sniprun's cache, for :SnipHistory
+        max_entries = 500,  -- only the most recent ones are kept
+    },
+
     ansi_escape = true,
     inline_messages = 0,
     borders = 'single',
@@ -59,6 +84,9 @@ M.config_values = {
         SniprunFloatingWinOk  = { default = true, fg = "#66eeff", ctermfg = "Cyan" },
         SniprunVirtualTextErr = { default = true, bg = "#881515", fg = "#000000", ctermbg = "DarkRed", ctermfg = "Black" },
         SniprunFloatingWinErr = { default = true, fg = "#881515", ctermfg = "DarkRed" },
+        SniprunTableHeader    = { default = true, link = "Title" },
+        SniprunDiffAdd        = { default = true, link = "DiffAdd" },
+        SniprunDiffDelete     = { default = true, link = "DiffDelete" },
     },
 
     -- whether the user can toggle the live_mode. It's kept as an option so it's not activated by chance
@@ -165,11 +193,25 @@ function M.configure_keymaps()
     vim.api.nvim_set_keymap("n", "<Plug>SnipInfo", ":lua require'sniprun'.info()<CR>", {})
     vim.api.nvim_set_keymap("n", "<Plug>SnipReplMemoryClean", ":lua require'sniprun'.clear_repl()<CR>", { silent = true })
     vim.api.nvim_set_keymap("n", "<Plug>SnipClose", ":lua require'sniprun.display'.close_all()<CR>", { silent = true })
+    vim.api.nvim_set_keymap("n", "<Plug>SnipShow", ":lua require'sniprun.display'.show_result()<CR>", { silent = true })
 
     vim.cmd("command! SnipReset :lua require'sniprun'.reset()")
     vim.cmd("command! SnipReplMemoryClean :lua require'sniprun'.clear_repl()")
     vim.cmd("function! SnipRunOperator(...) \n lua require'sniprun'.run('n') \n endfunction")
     vim.cmd("command! SnipClose :lua require'sniprun.display'.close_all()")
+    vim.cmd("command! SnipShow :lua require'sniprun.display'.show_result()")
+    vim.cmd("command! -nargs=? -complete=file SnipAttach :lua require'sniprun'.attach(<q-args>)")
+    vim.cmd("command! SnipDetach :lua require'sniprun'.detach()")
+    vim.cmd("function! ListSqlProfiles(A,L,P) \n return luaeval(\"require'sniprun'.sql_profiles()\") \n endfunction")
+    vim.cmd("command! -nargs=? -complete=customlist,ListSqlProfiles SnipSqlConnect :lua require'sniprun'.sql_connect(<q-args>)")
+    vim.cmd("function! ListHttpEnvironments(A,L,P) \n return luaeval(\"require'sniprun'.http_environments()\") \n endfunction")
+    vim.cmd("command! -nargs=? -complete=customlist,ListHttpEnvironments SnipHttpEnv :lua require'sniprun'.http_env(<q-args>)")
+    vim.cmd("function! ListHttpCookiesActions(A,L,P) \n return ['clear'] \n endfunction")
+    vim.cmd("command! -nargs=? -complete=customlist,ListHttpCookiesActions SnipHttpCookies :lua require'sniprun'.http_cookies(<q-args>)")
+    vim.cmd("command! -range -nargs=? SnipHttpToCurl :lua require'sniprun'.http_to_curl(<line1>, <line2>, <q-args>)")
+    vim.cmd("command! -range SnipCurlToHttp :lua require'sniprun'.curl_to_http(<line1>, <line2>)")
+    vim.cmd("function! ListHistoryActions(A,L,P) \n return ['clear'] \n endfunction")
+    vim.cmd("command! -nargs=? -complete=customlist,ListHistoryActions SnipHistory :lua require'sniprun'.history(<q-args>)")
 
     vim.cmd("function! ListInterpreters(A,L,P) \n let l = split(globpath('" ..
     M.config_values.sniprun_path ..
@@ -187,7 +229,14 @@ function M.start()
     M.setup_highlights() -- some configurations break highlights (lunarvim/lazy for example)
 end
 
+-- the directory the project files (.sniprun_sql_profiles, http-client.env.json...) are read from,
+-- by the completions here and by the interpreters (sent along with the config)
+function M.project_root()
+    return vim.fn.getcwd()
+end
+
 function M.notify(method, ...)
+    M.config_values.project_root = M.project_root()
     M.start()
     local status, err = pcall(vim.rpcnotify, M.job_id, method, ...)
     if not status then
@@ -234,6 +283,155 @@ function M.clear_repl()
     M.notify("clearrepl")
 end
 
+-- attach the jupyter interpreters to an already-running kernel (eg: a notebook's),
+-- using the given connection file or the most recent one in `jupyter --runtime-dir`
+function M.attach(connection_file)
+    if connection_file == nil or connection_file == "" then
+        local runtime_dir = vim.fn.trim(vim.fn.system({ "jupyter", "--runtime-dir" }))
+        local candidates = vim.fn.glob(runtime_dir .. "/kernel-*.json", false, true)
+        table.sort(candidates, function(a, b) return vim.fn.getftime(a) > vim.fn.getftime(b) end)
+        connection_file = candidates[1]
+        if connection_file == nil then
+            print("Sniprun: no jupyter kernel found in " .. runtime_dir)
+            return
+        end
+    end
+    connection_file = vim.fn.fnamemodify(connection_file, ":p")
+
+    local options = M.config_values.interpreter_options
+    local ft = vim.bo.filetype
+    if ft:match("^python") then
+        options.Python3_jupyter = options.Python3_jupyter or {}
+        options.Python3_jupyter.connection_file = connection_file
+    end
+    options.Jupyter_generic = options.Jupyter_generic or {}
+    options.Jupyter_generic.connection_files = options.Jupyter_generic.connection_files or {}
+    options.Jupyter_generic.connection_files[ft] = connection_file
+    print("Sniprun: attached to " .. connection_file)
+end
+
+function M.detach()
+    local options = M.config_values.interpreter_options
+    if options.Python3_jupyter then
+        options.Python3_jupyter.connection_file = nil
+    end
+    if options.Jupyter_generic and options.Jupyter_generic.connection_files then
+        options.Jupyter_generic.connection_files[vim.bo.filetype] = nil
+    end
+end
+
+-- names of the SQL_original connection profiles, from the config and the project file
+function M.sql_profiles()
+    local names = {}
+    local options = M.config_values.interpreter_options.SQL_original or {}
+    for name, _ in pairs(options.profiles or {}) do
+        table.insert(names, name)
+    end
+    local project_file = M.project_root() .. "/.sniprun_sql_profiles"
+    if vim.fn.filereadable(project_file) == 1 then
+        for _, line in ipairs(vim.fn.readfile(project_file)) do
+            local name = line:match("^%s*([^#=%s][^=]-)%s*=")
+            if name then
+                table.insert(names, name)
+            end
+        end
+    end
+    table.sort(names)
+    return names
+end
+
+-- select the SQL_original connection profile to use (unless overridden by a '-- sniprun: db=' comment)
+function M.sql_connect(profile)
+    local options = M.config_values.interpreter_options
+    options.SQL_original = options.SQL_original or {}
+    if profile ~= nil and profile ~= "" then
+        options.SQL_original.profile = profile
+        print("Sniprun: using SQL profile " .. profile)
+        return
+    end
+    vim.ui.select(M.sql_profiles(), { prompt = "SQL profile:" }, function(choice)
+        if choice then
+            M.sql_connect(choice)
+        end
+    end)
+end
+
+-- names of the environments defined in the project's http-client(.private).env.json
+function M.http_environments()
+    local names = {}
+    for _, file in ipairs({ "http-client.env.json", "http-client.private.env.json" }) do
+        local path = M.project_root() .. "/" .. file
+        if vim.fn.filereadable(path) == 1 then
+            local ok, environments = pcall(vim.fn.json_decode, vim.fn.readfile(path))
+            if ok and type(environments) == "table" then
+                for name, _ in pairs(environments) do
+                    if name ~= "$shared" and not vim.tbl_contains(names, name) then
+                        table.insert(names, name)
+                    end
+                end
+            end
+        end
+    end
+    table.sort(names)
+    return names
+end
+
+-- select the environment whose variables are used by Http_original
+function M.http_env(environment)
+    local options = M.config_values.interpreter_options
+    options.Http_original = options.Http_original or {}
+    if environment ~= nil and environment ~= "" then
+        options.Http_original.environment = environment
+        print("Sniprun: using http environment " .. environment)
+        return
+    end
+    vim.ui.select(M.http_environments(), { prompt = "Http environment:" }, function(choice)
+        if choice then
+            M.http_env(choice)
+        end
+    end)
+end
+
+-- show the cookies kept by Http_original, or clear them (action = "clear")
+function M.http_cookies(action)
+    M.config_values["sniprun_root_dir"] = M.config_values.sniprun_path
+    local line = vim.api.nvim_win_get_cursor(0)[1]
+    M.notify("httpcookies", line, line, M.config_values, action or "")
+end
+
+-- list the recorded runs to pick one from, or clear the history (action = "clear")
+function M.history(action)
+    M.config_values["sniprun_root_dir"] = M.config_values.sniprun_path
+    local line = vim.api.nvim_win_get_cursor(0)[1]
+    M.notify("history", line, line, M.config_values, action or "")
+end
+
+-- convert the request(s) in range (default: the one under the cursor, between '###' separators)
+-- to curl commands, shown in a floating window or yanked to the given register
+function M.http_to_curl(line1, line2, register)
+    if line1 == line2 then
+        while line1 > 1 and not vim.fn.getline(line1 - 1):match("^###") do
+            line1 = line1 - 1
+        end
+        while line2 < vim.fn.line("$") and not vim.fn.getline(line2 + 1):match("^###") do
+            line2 = line2 + 1
+        end
+    end
+    M.config_values["sniprun_root_dir"] = M.config_values.sniprun_path
+    M.notify("httpcurl", line1, line2, M.config_values, vim.fn.trim("to_curl " .. (register or "")))
+end
+
+-- replace the curl command in range (default: the one starting under the cursor) by .http syntax
+function M.curl_to_http(line1, line2)
+    if line1 == line2 then
+        while line2 < vim.fn.line("$") and vim.fn.getline(line2):match("\\%s*$") do
+            line2 = line2 + 1
+        end
+    end
+    M.config_values["sniprun_root_dir"] = M.config_values.sniprun_path
+    M.notify("httpcurl", line1, line2, M.config_values, "from_curl")
+end
+
 function M.ping()
     M.notify("ping")
 end
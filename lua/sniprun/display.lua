@@ -9,7 +9,169 @@ M.borders = "single"
 
 local NAMESPACE = "sniprun"
 
-function M.fw_open(row, column, message, ok, temp)
+-- xterm's colors, for the terminal colors not set by the colorscheme
+local ANSI_PALETTE = {
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+}
+
+local function ansi_color(color)
+    if type(color) == "number" then
+        return vim.g["terminal_color_" .. color] or ANSI_PALETTE[color + 1]
+    elseif color == "" then
+        return nil
+    end
+    return color
+end
+
+-- define the highlight group of an ANSI highlight (again, in case the colorscheme cleared it)
+-- h = {line, start, end, group, fg, bg, bold, italic, underline}, or {line, start, end, group} for sniprun's own groups
+local function ansi_group(h)
+    if #h <= 4 then
+        return h[4]
+    end
+    vim.api.nvim_set_hl(0, h[4], {
+        fg = ansi_color(h[5]),
+        bg = ansi_color(h[6]),
+        bold = h[7],
+        italic = h[8],
+        underline = h[9],
+    })
+    return h[4]
+end
+
+-- results of the runs, per buffer: bufnr -> run_id -> { mark, ok, message, highlights, virt, full_output }
+-- 'mark' is an extmark covering the code that was run, 'virt' the ids of its virtual text/lines,
+-- 'full_output' the file holding the whole output when it was truncated
+M.results = {}
+local RESULTS_NAMESPACE = "sniprun_results"
+local MAX_RESULTS = 100 -- per buffer
+
+local function buffer(bufnr)
+    return (bufnr == nil or bufnr == 0) and vim.api.nvim_get_current_buf() or bufnr
+end
+
+function M.record_result(bufnr, run_id, first_row, last_row, ok, message, highlights, full_output)
+    bufnr = buffer(bufnr)
+    if not vim.api.nvim_buf_is_valid(bufnr) then
+        return
+    end
+    local ns = vim.api.nvim_create_namespace(RESULTS_NAMESPACE)
+    local line_count = vim.api.nvim_buf_line_count(bufnr)
+    first_row = math.min(math.max(first_row, 0), line_count - 1)
+    last_row = math.min(math.max(last_row, first_row), line_count - 1)
+    M.results[bufnr] = M.results[bufnr] or {}
+    local results = M.results[bufnr]
+    results[run_id] = {
+        mark = vim.api.nvim_buf_set_extmark(bufnr, ns, first_row, 0, { end_row = last_row }),
+        ok = ok,
+        message = message,
+        highlights = highlights,
+        virt = {},
+        full_output = full_output,
+    }
+
+    local oldest = run_id - MAX_RESULTS
+    for id, result in pairs(results) do
+        if id <= oldest then
+            vim.api.nvim_buf_del_extmark(bufnr, ns, result.mark)
+            results[id] = nil
+        end
+    end
+end
+
+-- the results whose code currently covers (some of) the rows first_row..last_row, most recent first
+local function results_in(bufnr, first_row, last_row)
+    local ns = vim.api.nvim_create_namespace(RESULTS_NAMESPACE)
+    local found = {}
+    for id, result in pairs(M.results[bufnr] or {}) do
+        local pos = vim.api.nvim_buf_get_extmark_by_id(bufnr, ns, result.mark, { details = true })
+        if pos[1] ~= nil and pos[1] <= last_row and (pos[3].end_row or pos[1]) >= first_row then
+            table.insert(found, { id = id, result = result, first = pos[1], row = pos[3].end_row or pos[1] })
+        end
+    end
+    table.sort(found, function(a, b) return a.id > b.id end)
+    return found
+end
+
+-- remove the virtual text/lines of the previous results of the code now run
+function M.clear_previous_virtual(bufnr, ns, run_id)
+    bufnr = buffer(bufnr)
+    local current = (M.results[bufnr] or {})[run_id]
+    if current == nil then
+        return
+    end
+    local pos = vim.api.nvim_buf_get_extmark_by_id(bufnr, vim.api.nvim_create_namespace(RESULTS_NAMESPACE),
+        current.mark, { details = true })
+    if pos[1] == nil then
+        return
+    end
+    for _, found in ipairs(results_in(bufnr, pos[1], pos[3].end_row or pos[1])) do
+        if found.id ~= run_id then
+            for _, mark in ipairs(found.result.virt) do
+                vim.api.nvim_buf_del_extmark(bufnr, ns, mark)
+            end
+            found.result.virt = {}
+        end
+    end
+end
+
+-- the message of the most recent other result of exactly the same code (the same rows), if any
+function M.previous_result(bufnr, run_id)
+    bufnr = buffer(bufnr)
+    local current = (M.results[bufnr] or {})[run_id]
+    if current == nil then
+        return nil
+    end
+    local pos = vim.api.nvim_buf_get_extmark_by_id(bufnr, vim.api.nvim_create_namespace(RESULTS_NAMESPACE),
+        current.mark, { details = true })
+    if pos[1] == nil then
+        return nil
+    end
+    local last_row = pos[3].end_row or pos[1]
+    for _, found in ipairs(results_in(bufnr, pos[1], last_row)) do
+        if found.id ~= run_id and found.first == pos[1] and found.row == last_row then
+            return found.result.message
+        end
+    end
+    return nil
+end
+
+-- the row to display the virtual text/lines of a run on (the last line of its code), and remember the mark
+local function add_virtual(bufnr, run_id, row, place)
+    bufnr = buffer(bufnr)
+    local result = (M.results[bufnr] or {})[run_id]
+    if result ~= nil then
+        local pos = vim.api.nvim_buf_get_extmark_by_id(bufnr, vim.api.nvim_create_namespace(RESULTS_NAMESPACE),
+            result.mark, { details = true })
+        row = pos[3] and pos[3].end_row or pos[1] or row
+    end
+    local mark = place(bufnr, row)
+    if result ~= nil then
+        table.insert(result.virt, mark)
+    end
+end
+
+-- reopen the full output of the (most recent) result of the code under the cursor,
+-- in a split for the truncated ones
+function M.show_result()
+    local bufnr = vim.api.nvim_get_current_buf()
+    local row = vim.api.nvim_win_get_cursor(0)[1] - 1
+    local found = results_in(bufnr, row, row)[1]
+    if found == nil then
+        print("Sniprun: no result under the cursor")
+        return
+    end
+    local full_output = found.result.full_output
+    if full_output ~= nil and vim.fn.filereadable(full_output) == 1 then
+        vim.cmd("split " .. vim.fn.fnameescape(full_output))
+        return
+    end
+    local line = vim.api.nvim_buf_get_lines(bufnr, found.row, found.row + 1, false)[1] or ""
+    M.fw_open(found.row, #line, found.result.message, found.result.ok, found.result.highlights)
+end
+
+function M.fw_open(row, column, message, ok, highlights)
     M.fw_close()
 
     local hl_ok = "SniprunFloatingWinOk"
@@ -22,11 +184,20 @@ function M.fw_open(row, column, message, ok, temp)
     local h = -1
     local bp = { row, column }
     local bufnr = vim.api.nvim_create_buf(false, true)
+    local previous_line = nil
     for line in message:gmatch("([^\n]*)\n?") do
         h = h + 1
-        w = math.max(w, string.len(line))
+        w = math.max(w, vim.fn.strdisplaywidth(line))
         vim.api.nvim_buf_set_lines(bufnr, h, h + 1, false, { line })
         vim.api.nvim_buf_add_highlight(bufnr, namespace_id, hl, h, 0, -1) -- highlight lines in floating window
+        if previous_line and h > 0 and line ~= "" and line:match("^[─┼]+$") then
+            -- line under a table header
+            vim.api.nvim_buf_add_highlight(bufnr, namespace_id, "SniprunTableHeader", h - 1, 0, -1)
+        end
+        previous_line = line
+    end
+    for _, hl_ansi in ipairs(highlights or {}) do
+        vim.api.nvim_buf_add_highlight(bufnr, namespace_id, ansi_group(hl_ansi), hl_ansi[1], hl_ansi[2], hl_ansi[3])
     end
     if h ~= 0 then
         M.fw_handle = vim.api.nvim_open_win(bufnr, false, {
@@ -182,28 +353,120 @@ function M.display_nvim_notify(message, ok)
     })
 end
 
-function M.display_virt_text(ns, line, message, highlight)
-    vim.api.nvim_buf_set_extmark(0, ns, line, -1, { virt_text = { { message, highlight } } })
+function M.display_virt_text(ns, line, message, highlight, bufnr, run_id)
+    add_virtual(bufnr, run_id, line, function(buf, row)
+        return vim.api.nvim_buf_set_extmark(buf, ns, row, -1, { virt_text = { { message, highlight } } })
+    end)
 end
 
-function M.display_virt_line(ns, line_pos, message, highlight)
+function M.display_virt_line(ns, line_pos, message, highlight, highlights, bufnr, run_id, changed)
+    local is_changed = {}
+    for _, l in ipairs(changed or {}) do
+        is_changed[l] = true
+    end
+    local by_line = {}
+    for _, h in ipairs(highlights or {}) do
+        by_line[h[1]] = by_line[h[1]] or {}
+        table.insert(by_line[h[1]], h)
+    end
     local virt_lines = {}
+    local i = 0
     for line in message:gmatch("([^\n]*)\n?") do
-        table.insert(virt_lines, { { line, highlight } })
+        -- split the line in chunks at the ANSI highlights bounds
+        local line_hl = is_changed[i] and "SniprunDiffAdd" or highlight
+        local chunks = {}
+        local col = 0
+        for _, h in ipairs(by_line[i] or {}) do
+            if h[2] > col then
+                table.insert(chunks, { line:sub(col + 1, h[2]), line_hl })
+            end
+            table.insert(chunks, { line:sub(h[2] + 1, h[3]), ansi_group(h) })
+            col = h[3]
+        end
+        table.insert(chunks, { line:sub(col + 1), line_hl })
+        table.insert(virt_lines, chunks)
+        i = i + 1
+    end
+    add_virtual(bufnr, run_id, line_pos, function(buf, row)
+        return vim.api.nvim_buf_set_extmark(buf, ns, row, 0, { virt_lines = virt_lines })
+    end)
+end
+
+-- prefix and suffix of the lines inserted by 'InsertBelow', from the commentstring
+local function insert_below_affixes(bufnr)
+    local before, after = vim.bo[bufnr].commentstring:match("^(.-)%%s(.*)$")
+    if before == nil then
+        return "=> ", ""
+    end
+    if before ~= "" and not before:match("%s$") then
+        before = before .. " "
+    end
+    if after ~= "" and not after:match("^%s") then
+        after = " " .. after
+    end
+    return before .. "=> ", after
+end
+
+-- insert the output as comments under the line 'line' (1-based) of the buffer the code
+-- was run from, replacing the output previously inserted there (tracked with an extmark)
+function M.insert_below(line, message, bufnr)
+    if not vim.api.nvim_buf_is_valid(bufnr) then
+        return
+    end
+    local ns = vim.api.nvim_create_namespace("sniprun_insert_below")
+    local prefix, suffix = insert_below_affixes(bufnr)
+    local marks = {}
+    if line < vim.api.nvim_buf_line_count(bufnr) then
+        marks = vim.api.nvim_buf_get_extmarks(bufnr, ns, { line, 0 }, { line, 0 }, { details = true })
+    end
+    for _, mark in ipairs(marks) do
+        local previous = vim.api.nvim_buf_get_lines(bufnr, line, mark[4].end_row + 1, false)
+        local untouched = #previous > 0
+        for _, l in ipairs(previous) do
+            untouched = untouched and vim.startswith(l, prefix)
+        end
+        -- don't delete lines that are not sniprun's output anymore
+        if untouched then
+            vim.api.nvim_buf_set_lines(bufnr, line, mark[4].end_row + 1, false, {})
+        end
+        vim.api.nvim_buf_del_extmark(bufnr, ns, mark[1])
+    end
+    if message == "" then
+        return
     end
-    vim.api.nvim_buf_set_extmark(0, ns, line_pos, 0, {
-        virt_lines = virt_lines,
+
+    local lines = {}
+    for _, l in ipairs(vim.split(message, "\n", { plain = true })) do
+        table.insert(lines, prefix .. l .. suffix)
+    end
+    vim.api.nvim_buf_set_lines(bufnr, line, line, false, lines)
+    vim.api.nvim_buf_set_extmark(bufnr, ns, line, 0, {
+        end_row = line + #lines - 1,
+        end_col = #lines[#lines],
     })
 end
 
-function M.send_api(message, ok)
-    local d = {}
-    d.message = message
-    if ok then
-        d.status = "ok"
-    else
-        d.status = "error"
+-- replace the quickfix (or location) list by sniprun's items: a list of
+-- another origin is only replaced when there are items to show
+function M.set_list(loclist, items)
+    local title = "sniprun"
+    local get = loclist and function(what) return vim.fn.getloclist(0, what) end or vim.fn.getqflist
+    local set = loclist and function(...) return vim.fn.setloclist(0, ...) end or vim.fn.setqflist
+    local ours = get({ title = 0 }).title == title
+    if #items == 0 and not ours then
+        return
     end
+    set({}, ours and "r" or " ", { title = title, items = items })
+
+    local win = vim.api.nvim_get_current_win()
+    vim.cmd(loclist and "lwindow" or "cwindow")
+    vim.api.nvim_set_current_win(win)
+end
+
+-- d: table with the status ("ok" or "error"), message, images,
+-- error_kind, interpreter, filetype, bufnr, range, duration and run_id of the run
+function M.send_api(d)
+    d.images = d.images or {}
     local listeners = require("sniprun.api").listeners
 
     if type(next(listeners)) == "nil" then
@@ -215,6 +478,29 @@ function M.send_api(message, ok)
     end
 end
 
+function M.open_table_buffer(csv)
+    vim.cmd("new")
+    local bufnr = vim.api.nvim_get_current_buf()
+    vim.bo[bufnr].buftype = "nofile"
+    vim.bo[bufnr].bufhidden = "wipe"
+    vim.bo[bufnr].swapfile = false
+    vim.api.nvim_buf_set_lines(bufnr, 0, -1, false, vim.split(csv, "\n", { plain = true }))
+    vim.bo[bufnr].filetype = "csv"
+    vim.cmd("wincmd p")
+end
+
+function M.open_images(images)
+    local viewer = require("sniprun").config_values.display_options.image_viewer
+    if viewer == nil or viewer == "" then
+        return
+    end
+    for _, image in ipairs(images) do
+        local cmd = vim.split(viewer, " ", { trimempty = true })
+        table.insert(cmd, image)
+        vim.fn.jobstart(cmd, { detach = true })
+    end
+end
+
 function M.close_api()
     local listeners = require("sniprun.api").closers
     for i, f in ipairs(listeners) do
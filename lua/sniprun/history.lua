@@ -0,0 +1,70 @@
+local M = {}
+
+local function split(text)
+    return vim.split(text or "", "\n", { plain = true })
+end
+
+local function describe(entry)
+    local file = entry.file ~= "" and vim.fn.fnamemodify(entry.file, ":~:.") or "[No Name]"
+    local code = vim.trim(split(entry.code)[1] or "")
+    return string.format("%s  %-5s  %s:%d-%d  %s", os.date("%Y-%m-%d %H:%M:%S", entry.timestamp),
+        entry.status, file, entry.range[1], entry.range[2], code)
+end
+
+local function scratch(lines, name)
+    local bufnr = vim.api.nvim_get_current_buf()
+    vim.bo[bufnr].buftype = "nofile"
+    vim.bo[bufnr].bufhidden = "wipe"
+    vim.bo[bufnr].swapfile = false
+    vim.api.nvim_buf_set_lines(bufnr, 0, -1, false, lines)
+    pcall(vim.api.nvim_buf_set_name, bufnr, name) -- fails if already used by another diff
+end
+
+-- the most recent run of the same code (same file and lines, or same code), if it's not 'entry'
+local function latest_run(entries, entry)
+    for _, other in ipairs(entries) do
+        if other.file == entry.file and other.range[1] == entry.range[1] and other.range[2] == entry.range[2]
+            or other.code == entry.code then
+            return other ~= entry and other or nil
+        end
+    end
+end
+
+local function diff(entry, latest)
+    vim.cmd("tabnew")
+    scratch(split(entry.result), "sniprun://history/" .. os.date("%Y-%m-%d_%H:%M:%S", entry.timestamp))
+    vim.cmd("diffthis")
+    vim.cmd("vnew")
+    scratch(split(latest.result), "sniprun://history/latest")
+    vim.cmd("diffthis")
+end
+
+-- entries: the past runs, most recent first
+function M.pick(entries)
+    if #entries == 0 then
+        print("Sniprun: the history is empty")
+        return
+    end
+    vim.ui.select(entries, { prompt = "Sniprun history", format_item = describe }, function(entry)
+        if entry == nil then
+            return
+        end
+        local actions = { "Show the output", "Run again", "Diff with the latest run of this code" }
+        vim.ui.select(actions, { prompt = describe(entry) }, function(_, action)
+            if action == 1 then
+                require("sniprun").display_lines_in_floating_win(split(entry.result))
+            elseif action == 2 then
+                require("sniprun.api").run_string(entry.code, entry.filetype)
+            elseif action == 3 then
+                local latest = latest_run(entries, entry)
+                if latest == nil then
+                    print("Sniprun: this is the latest run of this code")
+                    return
+                end
+                diff(entry, latest)
+            end
+        end)
+    end)
+end
+
+return M
@@ -21,6 +21,8 @@ function M.run()
     local ft            = vim.bo.filetype
     local opts          = deepcopy(require('sniprun').config_values)
     opts.display        = opts.live_display
+    opts.display_rules  = {}
+    opts.history        = { enabled = false }
     opts.show_no_output = {}
     sa.run_range(line, line, ft, opts)
 end
@@ -0,0 +1,230 @@
+//! ANSI SGR (colors, bold...) escape sequences
+//!
+//! Colored outputs are converted to plain text and highlight ranges, that the
+//! floating window and virtual lines displays apply as highlights.
+
+use neovim_lib::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    /// one of the 256 colors of the terminal palette
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// the 16 first colors are the terminal's ones (resolved on the lua side),
+    /// the others are converted to "#rrggbb"
+    fn to_value(self) -> Value {
+        let (r, g, b) = match self {
+            Color::Indexed(i) if i < 16 => return Value::from(i),
+            Color::Indexed(i) if i < 232 => {
+                let level = |c: u8| if c == 0 { 0 } else { 55 + 40 * c };
+                let i = i - 16;
+                (level(i / 36), level(i / 6 % 6), level(i % 6))
+            }
+            Color::Indexed(i) => {
+                let gray = 8 + 10 * (i - 232);
+                (gray, gray, gray)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        };
+        Value::from(format!("#{:02x}{:02x}{:02x}", r, g, b))
+    }
+
+    fn name(self) -> String {
+        match self {
+            Color::Indexed(i) => i.to_string(),
+            Color::Rgb(r, g, b) => format!("{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    /// name of the highlight group (defined on the fly on the lua side)
+    pub fn group(&self) -> String {
+        let mut group = String::from("SniprunAnsi");
+        if let Some(fg) = self.fg {
+            group += &format!("Fg{}", fg.name());
+        }
+        if let Some(bg) = self.bg {
+            group += &format!("Bg{}", bg.name());
+        }
+        for (set, suffix) in [(self.bold, "B"), (self.italic, "I"), (self.underline, "U")] {
+            if set {
+                group += suffix;
+            }
+        }
+        group
+    }
+
+    fn apply(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|c| c.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.fg = Some(Color::Indexed(code as u8 - 30)),
+                90..=97 => self.fg = Some(Color::Indexed(code as u8 - 90 + 8)),
+                40..=47 => self.bg = Some(Color::Indexed(code as u8 - 40)),
+                100..=107 => self.bg = Some(Color::Indexed(code as u8 - 100 + 8)),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                38 | 48 => {
+                    let color = match codes.next() {
+                        Some(5) => codes.next().map(|i| Color::Indexed(i as u8)),
+                        Some(2) => {
+                            let mut c = || codes.next().unwrap_or(0) as u8;
+                            Some(Color::Rgb(c(), c(), c()))
+                        }
+                        _ => None,
+                    };
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// a styled range (bytes columns) of a line of the plain text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub style: Style,
+}
+
+/// remove the escape sequences from the text, and return the ranges styled by the SGR ones
+pub fn parse(text: &str) -> (String, Vec<Highlight>) {
+    let mut plain = String::with_capacity(text.len());
+    let mut highlights = vec![];
+    let mut style = Style::default();
+    let (mut line, mut line_start, mut run_start) = (0, 0, 0);
+
+    let mut close_run =
+        |plain: &String, line: usize, line_start: usize, run_start: usize, style: Style| {
+            let end = plain.len() - line_start;
+            if style != Style::default() && end > run_start {
+                highlights.push(Highlight {
+                    line,
+                    start: run_start,
+                    end,
+                    style,
+                });
+            }
+        };
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    // parameters, up to the final byte of the sequence
+                    for p in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&p) {
+                            if p == 'm' {
+                                close_run(&plain, line, line_start, run_start, style);
+                                run_start = plain.len() - line_start;
+                                style.apply(&params);
+                            }
+                            break;
+                        }
+                        params.push(p);
+                    }
+                }
+                Some(']') => {
+                    // OSC (ex: hyperlinks), ended by BEL or ESC \
+                    while let Some(p) = chars.next() {
+                        if p == '\x07' || (p == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => (),
+            },
+            '\n' => {
+                close_run(&plain, line, line_start, run_start, style);
+                plain.push('\n');
+                line += 1;
+                line_start = plain.len();
+                run_start = 0;
+            }
+            c => plain.push(c),
+        }
+    }
+    close_run(&plain, line, line_start, run_start, style);
+    (plain, highlights)
+}
+
+/// the highlights as a list of [line, start, end, group, fg, bg, bold, italic, underline],
+/// the missing colors being empty strings
+pub fn highlights_value(highlights: &[Highlight]) -> Value {
+    let color = |c: Option<Color>| c.map_or(Value::from(""), Color::to_value);
+    Value::Array(
+        highlights
+            .iter()
+            .map(|h| {
+                Value::Array(vec![
+                    Value::from(h.line),
+                    Value::from(h.start),
+                    Value::from(h.end),
+                    Value::from(h.style.group()),
+                    color(h.style.fg),
+                    color(h.style.bg),
+                    Value::from(h.style.bold),
+                    Value::from(h.style.italic),
+                    Value::from(h.style.underline),
+                ])
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test_ansi {
+    use super::*;
+
+    #[test]
+    fn sgr() {
+        let (plain, highlights) = parse(
+            "\x1b[1;31mFAIL\x1b[0m test\n\x1b[38;5;196mred\nstill\x1b[39m \x1b]8;;http://x\x07link",
+        );
+        assert_eq!(plain, "FAIL test\nred\nstill link");
+        assert_eq!(highlights.len(), 3);
+        assert_eq!(
+            (highlights[0].line, highlights[0].start, highlights[0].end),
+            (0, 0, 4)
+        );
+        assert_eq!(highlights[0].style.group(), "SniprunAnsiFg1B");
+        assert_eq!(
+            (highlights[2].line, highlights[2].start, highlights[2].end),
+            (2, 0, 5)
+        );
+        let value = highlights_value(&highlights[1..2]);
+        let red = value.as_array().unwrap()[0].as_array().unwrap();
+        assert_eq!(red[3].as_str(), Some("SniprunAnsiFg196"));
+        assert_eq!(red[4].as_str(), Some("#ff0000"));
+        assert_eq!(red[5].as_str(), Some(""));
+    }
+}
@@ -0,0 +1,124 @@
+//! Decoding of the outputs of the interpreters
+//!
+//! Outputs are decoded as UTF-8, lossily when they are not valid UTF-8 (ex: Latin-1).
+//! The binary outputs of the commands (raw bytes written by the snippet) are shown as a
+//! hexdump, while the outputs of the REPLs (see `read_output`) are always decoded as text.
+
+/// only the start of an output is checked for binary content
+const BINARY_SAMPLE_SIZE: usize = 8000;
+/// a few stray control characters or invalid bytes don't make an output binary
+const BINARY_MIN_SUSPICIOUS_BYTES: usize = 4;
+/// share of the sample that must be control characters or invalid UTF-8 bytes
+const BINARY_SUSPICIOUS_PERCENT: usize = 30;
+/// the hexdump of a binary output stops after that many bytes
+const HEXDUMP_MAX_BYTES: usize = 512;
+
+use std::io::Read;
+
+/// decode an output (stdout or stderr) of a process, without ever failing
+pub fn decode_output(bytes: &[u8]) -> String {
+    if is_binary(bytes) {
+        return format!("[binary output: {} bytes]\n{}", bytes.len(), hexdump(bytes));
+    }
+    decode_text(bytes)
+}
+
+/// decode as UTF-8, lossily if needed
+pub fn decode_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// `read_to_string` for the output files of the REPLs, that never fails on invalid UTF-8:
+/// no binary detection there, the start/end markers the REPLs wait for must stay readable
+pub fn read_output(file: &mut impl Read, output: &mut String) -> std::io::Result<usize> {
+    let mut bytes = vec![];
+    let read = file.read_to_end(&mut bytes)?;
+    output.push_str(&decode_text(&bytes));
+    Ok(read)
+}
+
+/// whether the output looks like binary data rather than (possibly badly encoded) text:
+/// a large share of its start is control characters or invalid UTF-8
+pub fn is_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(BINARY_SAMPLE_SIZE)];
+    // tabs, newlines, escape sequences... are common in text outputs
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x07 | 0x08 | 0x0c | 0x1b))
+        .count();
+    let suspicious = control + invalid_utf8_bytes(sample);
+    suspicious >= BINARY_MIN_SUSPICIOUS_BYTES
+        && suspicious * 100 > sample.len() * BINARY_SUSPICIOUS_PERCENT
+}
+
+/// number of bytes that are not part of a valid UTF-8 sequence
+/// (a sequence cut at the end of the sample is not counted)
+fn invalid_utf8_bytes(mut bytes: &[u8]) -> usize {
+    let mut invalid = 0;
+    while let Err(e) = std::str::from_utf8(bytes) {
+        match e.error_len() {
+            Some(len) => {
+                invalid += len;
+                bytes = &bytes[e.valid_up_to() + len..];
+            }
+            None => break,
+        }
+    }
+    invalid
+}
+
+/// `xxd`-like view of the bytes: offset, 16 bytes in hex, and their printable characters
+pub fn hexdump(bytes: &[u8]) -> String {
+    let shown = &bytes[..bytes.len().min(HEXDUMP_MAX_BYTES)];
+    let mut lines: Vec<String> = shown
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk
+                .chunks(2)
+                .map(|pair| pair.iter().map(|b| format!("{:02x}", b)).collect())
+                .collect();
+            let text: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}: {:<39}  {}", i * 16, hex.join(" "), text)
+        })
+        .collect();
+    if bytes.len() > shown.len() {
+        lines.push(format!("… {} more bytes", bytes.len() - shown.len()));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test_decode {
+    use super::*;
+
+    #[test]
+    fn outputs() {
+        assert_eq!(decode_output("héllo\n".as_bytes()), "héllo\n");
+        // latin-1
+        assert_eq!(decode_output(b"caf\xe9\n"), "caf\u{fffd}\n");
+        assert!(!is_binary(b"\x1b[31mred\x1b[0m\n"));
+        // stray NUL bytes are shown as text
+        assert_eq!(decode_output(b"a\0b"), "a\0b");
+        assert!(!is_binary(b"caf\xe9 cr\xe8me br\xfbl\xe9e\n"));
+
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00";
+        assert!(is_binary(png));
+        assert_eq!(
+            decode_output(png),
+            "[binary output: 17 bytes]\n\
+             00000000: 8950 4e47 0d0a 1a0a 0000 000d 4948 4452  .PNG........IHDR\n\
+             00000010: 00                                       ."
+        );
+        assert!(hexdump(&[0; 600]).ends_with("\n… 88 more bytes"));
+    }
+}
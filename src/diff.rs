@@ -0,0 +1,149 @@
+//! Line diffs between the outputs of two runs of the same code
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+use Change::*;
+
+/// above this (number of old lines x number of new lines), the outputs are
+/// considered entirely different instead of computing their longest common subsequence
+const MAX_LCS_SIZE: usize = 4_000_000;
+
+/// the changes turning 'old' into 'new', line by line
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    if old.len() * new.len() > MAX_LCS_SIZE {
+        return old
+            .iter()
+            .map(|&l| Removed(l))
+            .chain(new.iter().map(|&l| Added(l)))
+            .collect();
+    }
+
+    // lcs[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push(Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Added(new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// the (0-based) lines of 'new' that are not in 'old'
+pub fn changed_lines(old: &str, new: &str) -> Vec<usize> {
+    let mut line = 0;
+    let mut changed = vec![];
+    for change in diff_lines(old, new) {
+        match change {
+            Same(_) => line += 1,
+            Added(_) => {
+                changed.push(line);
+                line += 1;
+            }
+            Removed(_) => (),
+        }
+    }
+    changed
+}
+
+/// unified diff (with 'context' lines around the changes), empty if the outputs are the same
+pub fn unified(old: &str, new: &str, context: usize) -> String {
+    let changes = diff_lines(old, new);
+    let changed: Vec<usize> = (0..changes.len())
+        .filter(|&i| !matches!(changes[i], Same(_)))
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // ranges of the changes to show together
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for &i in changed.iter() {
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(changes.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = String::from("--- previous\n+++ current\n");
+    for (start, end) in hunks {
+        let count = |range: &[Change], old: bool| {
+            range
+                .iter()
+                .filter(|c| match c {
+                    Same(_) => true,
+                    Removed(_) => old,
+                    Added(_) => !old,
+                })
+                .count()
+        };
+        let (old_before, new_before) = (
+            count(&changes[..start], true),
+            count(&changes[..start], false),
+        );
+        let (old_len, new_len) = (
+            count(&changes[start..end], true),
+            count(&changes[start..end], false),
+        );
+        diff += &format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_before + (old_len > 0) as usize,
+            old_len,
+            new_before + (new_len > 0) as usize,
+            new_len
+        );
+        for change in &changes[start..end] {
+            diff += &match change {
+                Same(l) => format!(" {}\n", l),
+                Removed(l) => format!("-{}\n", l),
+                Added(l) => format!("+{}\n", l),
+            };
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod test_diff {
+    use super::*;
+
+    #[test]
+    fn unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh";
+        let new = "a\nb\nC\nd\ne\nf\ng\nh\ni";
+        assert_eq!(
+            unified(old, new, 1),
+            "--- previous\n+++ current\n@@ -2,3 +2,3 @@\n b\n-c\n+C\n d\n@@ -8,1 +8,2 @@\n h\n+i\n"
+        );
+        assert_eq!(unified(old, old, 3), "");
+        assert_eq!(changed_lines(old, new), vec![2, 8]);
+    }
+}
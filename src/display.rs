@@ -1,9 +1,16 @@
+use crate::ansi;
+use crate::diff;
 use crate::error::SniprunError;
-use crate::interpreter::index_from_name;
+use crate::interpreter::{index_from_name, IMAGE_MARKER};
+use crate::launcher::Launcher;
+use crate::quickfix;
+use crate::table;
 use crate::{DataHolder, ReturnMessageType};
 use log::info;
-use neovim_lib::{Neovim, NeovimApi};
+use neovim_lib::{Neovim, NeovimApi, Value};
+use regex::Regex;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use unindent::Unindent;
@@ -28,6 +35,10 @@ pub enum DisplayType {
     LongTempFloatingWindow(DisplayFilter),
     TempFloatingWindow(DisplayFilter),
     Api(DisplayFilter),
+    InsertBelow(DisplayFilter),
+    Quickfix(DisplayFilter),
+    Loclist(DisplayFilter),
+    Diff(DisplayFilter),
 }
 use DisplayType::*;
 
@@ -55,6 +66,10 @@ impl FromStr for DisplayType {
             "TempFloatingWindow" => Ok(TempFloatingWindow(display_filter)),
             "Api" => Ok(Api(display_filter)),
             "NvimNotify" => Ok(NvimNotify(display_filter)),
+            "InsertBelow" | "Comment" => Ok(InsertBelow(display_filter)),
+            "Quickfix" => Ok(Quickfix(display_filter)),
+            "Loclist" => Ok(Loclist(display_filter)),
+            "Diff" => Ok(Diff(display_filter)),
             _ => Err(SniprunError::InternalError(
                 "Invalid display type: ".to_string() + s,
             )),
@@ -91,13 +106,123 @@ impl fmt::Display for DisplayType {
             }
             DisplayType::Api(filter) => "Api".to_string() + &filter.to_string(),
             DisplayType::NvimNotify(filter) => "NvimNotify".to_string() + &filter.to_string(),
+            DisplayType::InsertBelow(filter) => "InsertBelow".to_string() + &filter.to_string(),
+            DisplayType::Quickfix(filter) => "Quickfix".to_string() + &filter.to_string(),
+            DisplayType::Loclist(filter) => "Loclist".to_string() + &filter.to_string(),
+            DisplayType::Diff(filter) => "Diff".to_string() + &filter.to_string(),
         };
         write!(f, "{}", name)
     }
 }
 
+/// A `display_rules` entry: the display types to use instead of the `display` ones,
+/// for the results matching all the (optional) conditions
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisplayRule {
+    pub filetype: Option<String>,
+    pub interpreter: Option<String>,
+    /// Some(true) for successful runs only, Some(false) for errors only
+    pub ok: Option<bool>,
+    pub min_lines: Option<usize>,
+    pub max_lines: Option<usize>,
+    pub display: Vec<DisplayType>,
+}
+
+impl DisplayRule {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let map = value.as_map()?;
+        let get = |key: &str| index_from_name(key, map).map(|i| &map[i].1);
+        let ok = match get("status").and_then(|v| v.as_str()) {
+            Some(s) if s.eq_ignore_ascii_case("ok") => Some(true),
+            Some(s) if s.eq_ignore_ascii_case("err") => Some(false),
+            Some(s) => {
+                info!("[DISPLAY] invalid display rule status: {}", s);
+                return None;
+            }
+            None => None,
+        };
+        let display = get("display")?
+            .as_array()?
+            .iter()
+            .filter_map(|v| v.as_str())
+            .filter_map(|s| DisplayType::from_str(s).ok())
+            .collect();
+        Some(DisplayRule {
+            filetype: get("filetype").and_then(|v| v.as_str()).map(String::from),
+            interpreter: get("interpreter")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            ok,
+            min_lines: get("min_lines")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize),
+            max_lines: get("max_lines")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize),
+            display,
+        })
+    }
+
+    fn matches(
+        &self,
+        filetype: &str,
+        interpreter: Option<&str>,
+        result: &Result<String, SniprunError>,
+    ) -> bool {
+        if self.filetype.as_ref().map_or(false, |f| f != filetype) {
+            return false;
+        }
+        if let Some(name) = &self.interpreter {
+            if interpreter != Some(name.as_str()) {
+                return false;
+            }
+        }
+        if self.ok.map_or(false, |ok| ok != result.is_ok()) {
+            return false;
+        }
+        let lines = match result {
+            Ok(message) => message.lines().count(),
+            Err(e) => e.to_string().lines().count(),
+        };
+        self.min_lines.map_or(true, |min| lines >= min)
+            && self.max_lines.map_or(true, |max| lines <= max)
+    }
+}
+
+/// name of the interpreter selected to run the code
+fn interpreter_name(data: &DataHolder) -> Option<String> {
+    Launcher::new(data.clone()).select().map(|(name, _)| name)
+}
+
+/// the display types to use for this result: those of the first matching
+/// `display_rules` entry, or the `display` ones
+fn effective_display_type(
+    result: &Result<String, SniprunError>,
+    data: &DataHolder,
+) -> Vec<DisplayType> {
+    if data.display_rules.is_empty() {
+        return data.display_type.clone();
+    }
+    let interpreter = if data.display_rules.iter().any(|r| r.interpreter.is_some()) {
+        interpreter_name(data)
+    } else {
+        None
+    };
+    match data
+        .display_rules
+        .iter()
+        .find(|rule| rule.matches(&data.filetype, interpreter.as_deref(), result))
+    {
+        Some(rule) => {
+            info!("[DISPLAY] using display rule {:?}", rule);
+            rule.display.clone()
+        }
+        None => data.display_type.clone(),
+    }
+}
+
 pub fn display(result: Result<String, SniprunError>, nvim: Arc<Mutex<Neovim>>, data: &DataHolder) {
-    let mut display_type = data.display_type.clone();
+    let mut display_type = effective_display_type(&result, data);
     display_type.sort();
     display_type.dedup(); //now only uniques display types
 
@@ -112,41 +237,151 @@ pub fn display(result: Result<String, SniprunError>, nvim: Arc<Mutex<Neovim>>, d
     }
 
     info!("Display type chosen: {:?}", display_type);
+    let images = captured_images(&result);
+    if !images.is_empty() {
+        open_images(&images, &nvim);
+    }
+    let result = render_tables(result, &nvim, data);
+    let (result, full_output) = limit_output(result, data);
+    // fetched before this result is recorded
+    let previous = if display_type.iter().any(|dt| matches!(dt, Diff(_))) {
+        previous_output(&nvim, data)
+    } else {
+        None
+    };
+    record_result(&result, full_output.as_deref(), &nvim, data);
     for dt in display_type.iter() {
         match dt {
             Classic(f) => {
                 return_message_classic(&result, &nvim, &data.return_message_type, data, *f)
             }
             VirtualText(f) => display_virtual_text(&result, &nvim, data, *f),
-            VirtualLine(f) => display_virtual_line(&result, &nvim, data, *f),
+            VirtualLine(f) => display_virtual_line(&result, previous.as_deref(), &nvim, data, *f),
             Terminal(f) => display_terminal(&result, &nvim, data, *f),
             TerminalWithCode(f) => display_terminal_with_code(&result, &nvim, data, *f),
             LongTempFloatingWindow(f) => display_floating_window(&result, &nvim, data, true, *f),
             TempFloatingWindow(f) => display_floating_window(&result, &nvim, data, false, *f),
             Api(f) => send_api(&result, &nvim, data, *f),
             NvimNotify(f) => display_nvim_notify(&result, &nvim, data, *f),
+            InsertBelow(f) => display_insert_below(&result, &nvim, data, *f),
+            Quickfix(f) => display_quickfix(&result, &nvim, data, *f, false),
+            Loclist(f) => display_quickfix(&result, &nvim, data, *f, true),
+            Diff(f) => display_diff(&result, previous.as_deref(), &nvim, data, *f),
         }
     }
 }
 
+/// call a function of the 'sniprun.display' lua module, the arguments being
+/// passed as msgpack values (so any output is displayed as-is, without escaping)
+fn call_display(
+    nvim: &Arc<Mutex<Neovim>>,
+    function: &str,
+    args: Vec<Value>,
+) -> Result<Value, Value> {
+    nvim.lock().unwrap().session.call(
+        "nvim_exec_lua",
+        vec![
+            Value::from(format!("return require'sniprun.display'.{}(...)", function)),
+            Value::Array(args),
+        ],
+    )
+}
+
+/// keep the result (anchored to the code that was run) on the lua side, for `:SnipShow`
+fn record_result(
+    result: &Result<String, SniprunError>,
+    full_output: Option<&Path>,
+    nvim: &Arc<Mutex<Neovim>>,
+    data: &DataHolder,
+) {
+    if data.run_id == 0 {
+        return;
+    }
+    let text = match result {
+        Ok(message) => message.to_string(),
+        Err(e) => e.to_string(),
+    };
+    let (message, highlights) = ansi_highlights(&text, data);
+    let res = call_display(
+        nvim,
+        "record_result",
+        vec![
+            Value::from(data.bufnr),
+            Value::from(data.run_id),
+            Value::from(data.range[0] - 1),
+            Value::from(data.range[1] - 1),
+            Value::from(result.is_ok()),
+            Value::from(cleanup(&message, false)),
+            highlights,
+            full_output.map_or(Value::Nil, |p| Value::from(p.to_string_lossy().as_ref())),
+        ],
+    );
+    info!("record result res = {:?}", res);
+}
+
+/// the output of the previous run of the same code (same lines of the same buffer), if any
+fn previous_output(nvim: &Arc<Mutex<Neovim>>, data: &DataHolder) -> Option<String> {
+    if data.run_id == 0 {
+        return None;
+    }
+    let res = call_display(
+        nvim,
+        "previous_result",
+        vec![Value::from(data.bufnr), Value::from(data.run_id)],
+    );
+    info!("previous result res = {:?}", res);
+    res.ok()?.as_str().map(|previous| cleanup(previous, true))
+}
+
+/// clear the virtual text/lines of the previous results of the code being displayed
+fn clear_previous_virtual(nvim: &Arc<Mutex<Neovim>>, namespace_id: i64, data: &DataHolder) {
+    let res = if data.run_id != 0 {
+        call_display(
+            nvim,
+            "clear_previous_virtual",
+            vec![
+                Value::from(data.bufnr),
+                Value::from(namespace_id),
+                Value::from(data.run_id),
+            ],
+        )
+    } else {
+        nvim.lock().unwrap().session.call(
+            "nvim_buf_clear_namespace",
+            vec![
+                Value::from(0),
+                Value::from(namespace_id),
+                Value::from(data.range[0] - 1),
+                Value::from(data.range[1]),
+            ],
+        )
+    };
+    info!("cleared previous virtual text/lines: {:?}", res);
+}
+
 pub fn display_nvim_notify(
     message: &Result<String, SniprunError>,
     nvim: &Arc<Mutex<Neovim>>,
     data: &DataHolder,
     filter: DisplayFilter,
 ) {
-    let res = match (message, filter) {
-        (Ok(result), OnlyOk) | (Ok(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".display_nvim_notify(\"{}\", true)",
-            no_output_wrap(result, data, &DisplayType::NvimNotify(filter)).replace('\n', "\\\n"),
-        )),
-        (Err(result), OnlyErr) | (Err(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".display_nvim_notify(\"{}\", false)",
-            no_output_wrap(&result.to_string(), data, &DisplayType::NvimNotify(filter))
-                .replace('\n', "\\\n"),
-        )),
-        _ => Ok(()),
+    let (message, ok) = match (message, filter) {
+        (Ok(result), OnlyOk) | (Ok(result), Both) => (result.to_string(), true),
+        (Err(result), OnlyErr) | (Err(result), Both) => (result.to_string(), false),
+        _ => return,
     };
+    let res = call_display(
+        nvim,
+        "display_nvim_notify",
+        vec![
+            Value::from(no_output_wrap(
+                &message,
+                data,
+                &DisplayType::NvimNotify(filter),
+            )),
+            Value::from(ok),
+        ],
+    );
     info!("display notify res = {:?}", res);
 }
 
@@ -156,24 +391,43 @@ pub fn send_api(
     data: &DataHolder,
     filter: DisplayFilter,
 ) {
-    let res = match (message, filter) {
-        (Ok(result), OnlyOk) | (Ok(result), Both) => {
-            let mut nvim_instance = nvim.lock().unwrap();
-            nvim_instance.command(&format!(
-                "lua require\"sniprun.display\".send_api(\"{}\", true)",
-                no_output_wrap(result, data, &DisplayType::Api(filter)).replace('\n', "\\\n"),
-            ))
-        }
-        (Err(result), OnlyErr) | (Err(result), Both) => {
-            let mut nvim_instance = nvim.lock().unwrap();
-            nvim_instance.command(&format!(
-                "lua require\"sniprun.display\".send_api(\"{}\", false)",
-                no_output_wrap(&result.to_string(), data, &DisplayType::Api(filter))
-                    .replace('\n', "\\\n"),
-            ))
-        }
-        _ => Ok(()),
+    let (text, ok) = match (message, filter) {
+        (Ok(result), OnlyOk) | (Ok(result), Both) => (result.to_string(), true),
+        (Err(result), OnlyErr) | (Err(result), Both) => (result.to_string(), false),
+        _ => return,
     };
+    let mut payload = vec![
+        ("status", Value::from(if ok { "ok" } else { "error" })),
+        (
+            "message",
+            Value::from(no_output_wrap(&text, data, &DisplayType::Api(filter))),
+        ),
+        ("images", string_list(&captured_images(message))),
+        (
+            "interpreter",
+            Value::from(interpreter_name(data).unwrap_or_default()),
+        ),
+        ("filetype", Value::from(data.filetype.as_str())),
+        ("bufnr", Value::from(data.bufnr)),
+        (
+            "range",
+            Value::Array(vec![Value::from(data.range[0]), Value::from(data.range[1])]),
+        ),
+        ("run_id", Value::from(data.run_id)),
+    ];
+    if let Err(e) = message {
+        payload.push(("error_kind", Value::from(e.kind())));
+    }
+    if let Some(duration) = data.run_duration {
+        payload.push(("duration", Value::from(duration.as_millis() as u64)));
+    }
+    let payload = Value::Map(
+        payload
+            .into_iter()
+            .map(|(k, v)| (Value::from(k), v))
+            .collect(),
+    );
+    let res = call_display(nvim, "send_api", vec![payload]);
     if res.is_ok() {
         info!("done displaying api");
     } else {
@@ -181,8 +435,168 @@ pub fn send_api(
     }
 }
 
+/// default limits of the displayed outputs (`output_max_lines` and `output_max_bytes`
+/// display options, 0 meaning no limit)
+const DEFAULT_OUTPUT_MAX_LINES: usize = 5000;
+const DEFAULT_OUTPUT_MAX_BYTES: usize = 1_000_000;
+/// as many full outputs as results are kept for `:SnipShow`
+const MAX_FULL_OUTPUTS: usize = 100;
+
+/// truncate the outputs above the size limits, the full output being written
+/// to a file of the work directory (also returned)
+fn limit_output(
+    result: Result<String, SniprunError>,
+    data: &DataHolder,
+) -> (Result<String, SniprunError>, Option<PathBuf>) {
+    let (max_lines, max_bytes) = output_limits(data);
+    let mut full_output = None;
+    let mut truncate = |message: String| {
+        let (shown, hidden_lines) = match truncated(&message, max_lines, max_bytes) {
+            Some(truncated) => truncated,
+            None => return message,
+        };
+        let mut marker = truncation_marker(hidden_lines);
+        let path = full_output_file(data);
+        match std::fs::write(&path, &message) {
+            Ok(()) => {
+                marker += &format!(", full output in {}", path.display());
+                full_output = Some(path);
+            }
+            Err(e) => info!("[DISPLAY] could not write the full output: {}", e),
+        }
+        format!("{}\n{}", shown, marker)
+    };
+    let result = match result {
+        Ok(message) => Ok(truncate(message)),
+        Err(e) => Err(e.map_message(&mut truncate)),
+    };
+    info!("[DISPLAY] full output: {:?}", full_output);
+    (result, full_output)
+}
+
+/// the `output_max_lines` and `output_max_bytes` display options
+fn output_limits(data: &DataHolder) -> (usize, usize) {
+    let limit = |key: &str, default: usize| {
+        display_option(data, key)
+            .and_then(|v| v.as_u64())
+            .map_or(default, |n| n as usize)
+    };
+    (
+        limit("output_max_lines", DEFAULT_OUTPUT_MAX_LINES),
+        limit("output_max_bytes", DEFAULT_OUTPUT_MAX_BYTES),
+    )
+}
+
+fn truncation_marker(hidden_lines: usize) -> String {
+    match hidden_lines {
+        0 => String::from("… (truncated)"),
+        n => format!("… {} more lines", n),
+    }
+}
+
+/// the message truncated to the given limits, with the same marker as the displayed outputs
+pub fn capped_output(message: &str, max_lines: usize, max_bytes: usize) -> String {
+    match truncated(message, max_lines, max_bytes) {
+        Some((shown, hidden_lines)) => format!("{}\n{}", shown, truncation_marker(hidden_lines)),
+        None => message.to_string(),
+    }
+}
+
+/// the start of the message within the limits and the number of lines left out,
+/// or None if the message is within them
+fn truncated(message: &str, max_lines: usize, max_bytes: usize) -> Option<(&str, usize)> {
+    let mut end = message.len();
+    if max_lines > 0 {
+        if let Some((i, _)) = message.match_indices('\n').nth(max_lines - 1) {
+            end = i;
+        }
+    }
+    if max_bytes > 0 && end > max_bytes {
+        end = max_bytes;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+    }
+    if message[end..].trim_end_matches('\n').is_empty() {
+        return None;
+    }
+    let shown = &message[..end];
+    Some((shown, message.lines().count() - shown.lines().count()))
+}
+
+/// the file to write the full output of this run to, removing the older ones
+fn full_output_file(data: &DataHolder) -> PathBuf {
+    if let Ok(entries) = std::fs::read_dir(&data.work_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let id: Option<usize> = entry.file_name().to_str().and_then(|f| {
+                f.strip_prefix("full_output_")?
+                    .strip_suffix(".txt")?
+                    .parse()
+                    .ok()
+            });
+            if matches!(id, Some(id) if id + MAX_FULL_OUTPUTS <= data.run_id) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+    PathBuf::from(&data.work_dir).join(format!("full_output_{}.txt", data.run_id))
+}
+
+/// render the tables embedded in the result as aligned columns,
+/// and open them as csv in scratch buffers if the user asked for it
+fn render_tables(
+    result: Result<String, SniprunError>,
+    nvim: &Arc<Mutex<Neovim>>,
+    data: &DataHolder,
+) -> Result<String, SniprunError> {
+    let message = result?;
+    let tables = table::extract_tables(&message);
+    if tables.is_empty() {
+        return Ok(message);
+    }
+
+    let open_scratch = display_option(data, "table_scratch_buffer")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if open_scratch {
+        for t in tables.iter() {
+            let res = call_display(nvim, "open_table_buffer", vec![Value::from(t.to_csv())]);
+            info!("open table buffer res = {:?}", res);
+        }
+    }
+
+    let max_width = display_option(data, "table_column_width")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(30) as usize;
+    Ok(table::render_tables(&message, max_width))
+}
+
+/// paths of the images reported (with IMAGE_MARKER) by the interpreter in its output
+pub fn captured_images(result: &Result<String, SniprunError>) -> Vec<String> {
+    match result {
+        Ok(message) => message
+            .lines()
+            .filter_map(|l| l.strip_prefix(IMAGE_MARKER))
+            .map(|path| path.trim().to_string())
+            .filter(|path| std::path::Path::new(path).exists())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// open the captured images with the viewer configured in display_options (if any)
+fn open_images(images: &[String], nvim: &Arc<Mutex<Neovim>>) {
+    let res = call_display(nvim, "open_images", vec![string_list(images)]);
+    info!("open images res = {:?}", res);
+}
+
+fn string_list(items: &[String]) -> Value {
+    Value::Array(items.iter().map(|s| Value::from(s.as_str())).collect())
+}
+
 pub fn display_virtual_line(
     result: &Result<String, SniprunError>,
+    previous: Option<&str>,
     nvim: &Arc<Mutex<Neovim>>,
     data: &DataHolder,
     filter: DisplayFilter,
@@ -194,72 +608,130 @@ pub fn display_virtual_line(
             return; // without clearing the line
         }
         // clear the current line
-        let last_line = data.range[1] - 1;
-        let _ = nvim.lock().unwrap().command(&format!(
-            "call nvim_buf_clear_namespace(0,{},{},{})",
-            namespace_id,
-            data.range[0] - 1,
-            last_line + 1
-        ));
-
+        clear_previous_virtual(nvim, namespace_id, data);
         return; //don't display unasked-for things
     }
 
     info!("namespace_id = {:?}", namespace_id);
+    clear_previous_virtual(nvim, namespace_id, data);
 
-    let last_line = data.range[1] - 1;
-    let res = nvim.lock().unwrap().command(&format!(
-        "call nvim_buf_clear_namespace(0,{},{},{})",
-        namespace_id,
-        data.range[0] - 1,
-        last_line + 1
-    ));
-    info!("cleared previous virtual_line? {:?}", res);
-
-    let hl_ok = "SniprunVirtualTextOk";
-    let hl_err = "SniprunVirtualTextErr";
-    let res = match (result, filter) {
+    let (message, hl) = match (result, filter) {
         (Ok(message_ok), OnlyOk) | (Ok(message_ok), Both) => {
-            if no_output_wrap(message_ok, data, &DisplayType::VirtualLine(filter)).is_empty() {
-                return;
-            }
-            nvim.lock().unwrap().command(&format!(
-                "lua require\"sniprun.display\".display_virt_line({},{},\"{}\",\"{}\")",
-                namespace_id,
-                last_line,
-                &no_output_wrap(message_ok, data, &DisplayType::VirtualLine(filter))
-                    .replace('\n', "\\\n"),
-                hl_ok
-            ))
+            (message_ok.to_string(), "SniprunVirtualTextOk")
         }
         (Err(message_err), OnlyErr) | (Err(message_err), Both) => {
-            if no_output_wrap(
-                &message_err.to_string(),
-                data,
-                &DisplayType::VirtualLine(filter),
-            )
-            .is_empty()
-            {
-                return;
-            }
-            nvim.lock().unwrap().command(&format!(
-                "lua require\"sniprun.display\".display_virt_line({},{},\"{}\",\"{}\")",
-                namespace_id,
-                last_line,
-                &no_output_wrap(
-                    &message_err.to_string(),
-                    data,
-                    &DisplayType::VirtualLine(filter)
-                )
-                .replace('\n', "\\\n"),
-                hl_err
-            ))
-        }
-        _ => Ok(()),
+            (message_err.to_string(), "SniprunVirtualTextErr")
+        }
+        _ => return,
     };
+    let (message, highlights) = ansi_highlights(&message, data);
+    let message = no_output_wrap(&message, data, &DisplayType::VirtualLine(filter));
+    if message.is_empty() {
+        return;
+    }
+    // with the Diff display, the lines that changed since the previous run get marked
+    let changed = previous.map_or(vec![], |previous| {
+        diff::changed_lines(previous, &strip_ansi_escapes::strip_str(&message))
+    });
+    let res = call_display(
+        nvim,
+        "display_virt_line",
+        vec![
+            Value::from(namespace_id),
+            Value::from(data.range[1] - 1),
+            Value::from(message),
+            Value::from(hl),
+            highlights,
+            Value::from(data.bufnr),
+            Value::from(data.run_id),
+            Value::Array(changed.into_iter().map(Value::from).collect()),
+        ],
+    );
     info!("done displaying virtual lines, {:?}", res);
 }
 
+pub fn display_insert_below(
+    result: &Result<String, SniprunError>,
+    nvim: &Arc<Mutex<Neovim>>,
+    data: &DataHolder,
+    filter: DisplayFilter,
+) {
+    let message = match (result, filter) {
+        (Ok(message), OnlyOk) | (Ok(message), Both) => {
+            no_output_wrap(message, data, &DisplayType::InsertBelow(filter))
+        }
+        (Err(e), OnlyErr) | (Err(e), Both) => {
+            no_output_wrap(&e.to_string(), data, &DisplayType::InsertBelow(filter))
+        }
+        _ => return,
+    };
+    // the previous output is replaced, even by an empty one
+    let res = call_display(
+        nvim,
+        "insert_below",
+        vec![
+            Value::from(data.range[1]),
+            Value::from(message),
+            Value::from(data.bufnr),
+        ],
+    );
+    info!("insert below res = {:?}", res);
+}
+
+/// fill the quickfix (or location) list with the error locations found in the output
+pub fn display_quickfix(
+    result: &Result<String, SniprunError>,
+    nvim: &Arc<Mutex<Neovim>>,
+    data: &DataHolder,
+    filter: DisplayFilter,
+    loclist: bool,
+) {
+    let (entries, kind) = match (result, filter) {
+        (Ok(message), OnlyOk) | (Ok(message), Both) => {
+            (quickfix::parse_locations(message, data), "I")
+        }
+        (Err(e), OnlyErr) | (Err(e), Both) => {
+            (quickfix::parse_locations(&e.to_string(), data), "E")
+        }
+        // a previous sniprun list gets cleared
+        _ => (vec![], ""),
+    };
+    let bufnr = {
+        let mut nvim_instance = nvim.lock().unwrap();
+        nvim_instance
+            .get_current_buf()
+            .and_then(|b| b.get_number(&mut nvim_instance))
+            .unwrap_or(0)
+    };
+    let items = entries
+        .into_iter()
+        .map(|e| {
+            let location = match e.filename {
+                Some(filename) => ("filename", Value::from(filename)),
+                None => ("bufnr", Value::from(bufnr)),
+            };
+            Value::Map(
+                vec![
+                    location,
+                    ("lnum", Value::from(e.lnum)),
+                    ("col", Value::from(e.col)),
+                    ("text", Value::from(e.text)),
+                    ("type", Value::from(kind)),
+                ]
+                .into_iter()
+                .map(|(k, v)| (Value::from(k), v))
+                .collect(),
+            )
+        })
+        .collect();
+    let res = call_display(
+        nvim,
+        "set_list",
+        vec![Value::from(loclist), Value::Array(items)],
+    );
+    info!("quickfix res = {:?}", res);
+}
+
 pub fn display_virtual_text(
     result: &Result<String, SniprunError>,
     nvim: &Arc<Mutex<Neovim>>,
@@ -273,77 +745,47 @@ pub fn display_virtual_text(
             return; // without clearing the line
         }
         // clear the current line
-        let last_line = data.range[1] - 1;
-        let _ = nvim.lock().unwrap().command(&format!(
-            "call nvim_buf_clear_namespace(0,{},{},{})",
-            namespace_id,
-            data.range[0] - 1,
-            last_line + 1
-        ));
-
+        clear_previous_virtual(nvim, namespace_id, data);
         return; //don't display unasked-for things
     }
 
     info!("namespace_id = {:?}", namespace_id);
+    clear_previous_virtual(nvim, namespace_id, data);
 
-    let last_line = data.range[1] - 1;
-    let res = nvim.lock().unwrap().command(&format!(
-        "call nvim_buf_clear_namespace(0,{},{},{})",
-        namespace_id,
-        data.range[0] - 1,
-        last_line + 1
-    ));
-    info!("cleared previous virtual_text? {:?}", res);
-
-    let hl_ok = "SniprunVirtualTextOk";
-    let hl_err = "SniprunVirtualTextErr";
-    let res = match (result, filter) {
-        (Ok(message_ok), OnlyOk) | (Ok(message_ok), Both) => {
-            if shorten_ok(&no_output_wrap(
+    let (message, hl) = match (result, filter) {
+        (Ok(message_ok), OnlyOk) | (Ok(message_ok), Both) => (
+            VirtualTextFormat::new(data, true).format(&no_output_wrap(
                 message_ok,
                 data,
                 &DisplayType::VirtualText(filter),
-            ))
-            .is_empty()
-            {
-                return;
-            }
-            nvim.lock().unwrap().command(&format!(
-                "lua require\"sniprun.display\".display_virt_text({},{},\"{}\",\"{}\")",
-                namespace_id,
-                last_line,
-                shorten_ok(&no_output_wrap(
-                    message_ok,
-                    data,
-                    &DisplayType::VirtualText(filter)
-                )),
-                hl_ok
-            ))
-        }
-        (Err(message_err), OnlyErr) | (Err(message_err), Both) => {
-            if shorten_err(&no_output_wrap(
+            )),
+            "SniprunVirtualTextOk",
+        ),
+        (Err(message_err), OnlyErr) | (Err(message_err), Both) => (
+            VirtualTextFormat::new(data, false).format(&no_output_wrap(
                 &message_err.to_string(),
                 data,
                 &DisplayType::VirtualText(filter),
-            ))
-            .is_empty()
-            {
-                return;
-            }
-            nvim.lock().unwrap().command(&format!(
-                "lua require\"sniprun.display\".display_virt_text({},{},\"{}\",\"{}\")",
-                namespace_id,
-                last_line,
-                shorten_err(&no_output_wrap(
-                    &message_err.to_string(),
-                    data,
-                    &DisplayType::VirtualText(filter)
-                )),
-                hl_err
-            ))
-        }
-        _ => Ok(()),
+            )),
+            "SniprunVirtualTextErr",
+        ),
+        _ => return,
     };
+    if message.is_empty() {
+        return;
+    }
+    let res = call_display(
+        nvim,
+        "display_virt_text",
+        vec![
+            Value::from(namespace_id),
+            Value::from(data.range[1] - 1),
+            Value::from(message),
+            Value::from(hl),
+            Value::from(data.bufnr),
+            Value::from(data.run_id),
+        ],
+    );
     info!("done displaying virtual text, {:?}", res);
 }
 
@@ -357,18 +799,23 @@ pub fn display_terminal(
     let a = data.current_bloc.lines();
     info!("length = {}", a.count());
     info!("display terminal, with filter: = {:?}", filter);
-    let res = match (message, filter) {
-        (Ok(result), OnlyOk) | (Ok(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".write_to_term(\"{}\", true)",
-            no_output_wrap(result, data, &DisplayType::Terminal(filter)).replace('\n', "\\\n"),
-        )),
-        (Err(result), OnlyErr) | (Err(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".write_to_term(\"{}\", false)",
-            no_output_wrap(&result.to_string(), data, &DisplayType::Terminal(filter))
-                .replace('\n', "\\\n"),
-        )),
-        _ => Ok(()),
+    let (message, ok) = match (message, filter) {
+        (Ok(result), OnlyOk) | (Ok(result), Both) => (result.to_string(), true),
+        (Err(result), OnlyErr) | (Err(result), Both) => (result.to_string(), false),
+        _ => return,
     };
+    let res = call_display(
+        nvim,
+        "write_to_term",
+        vec![
+            Value::from(no_output_wrap(
+                &message,
+                data,
+                &DisplayType::Terminal(filter),
+            )),
+            Value::from(ok),
+        ],
+    );
     info!("display terminal res = {:?}", res);
 }
 
@@ -378,43 +825,32 @@ pub fn display_terminal_with_code(
     data: &DataHolder,
     filter: DisplayFilter,
 ) {
-    let res = match (message, filter) {
-        (Ok(result), OnlyOk) | (Ok(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".write_to_term(\"{}\\n{}\", true)",
-            cleanup_and_escape(
-                &format!("\n{}", &data.current_bloc)
-                    .unindent()
-                    .lines()
-                    .fold("".to_string(), |cur_bloc, line_in_bloc| {
-                        cur_bloc + "> " + line_in_bloc + "\n"
-                    }),
-                ansi_option(data)
-            )
-            .replace('\n', "\\\n"),
-            no_output_wrap(result, data, &DisplayType::TerminalWithCode(filter))
-                .replace('\n', "\\\n"),
-        )),
-        (Err(result), OnlyErr) | (Err(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".write_to_term(\"{}\\n{}\", false)",
-            cleanup_and_escape(
-                &format!("\n{}", &data.current_bloc)
-                    .unindent()
-                    .lines()
-                    .fold("".to_string(), |cur_bloc, line_in_bloc| {
-                        cur_bloc + "> " + line_in_bloc + "\n"
-                    }),
-                ansi_option(data)
-            )
-            .replace('\n', "\\\n"),
-            no_output_wrap(
-                &result.to_string(),
-                data,
-                &DisplayType::TerminalWithCode(filter)
-            )
-            .replace('\n', "\\\n"),
-        )),
-        _ => Ok(()),
+    let (message, ok) = match (message, filter) {
+        (Ok(result), OnlyOk) | (Ok(result), Both) => (result.to_string(), true),
+        (Err(result), OnlyErr) | (Err(result), Both) => (result.to_string(), false),
+        _ => return,
     };
+    let code = cleanup(
+        &format!("\n{}", &data.current_bloc)
+            .unindent()
+            .lines()
+            .fold("".to_string(), |cur_bloc, line_in_bloc| {
+                cur_bloc + "> " + line_in_bloc + "\n"
+            }),
+        ansi_option(data),
+    );
+    let res = call_display(
+        nvim,
+        "write_to_term",
+        vec![
+            Value::from(format!(
+                "{}\n{}",
+                code,
+                no_output_wrap(&message, data, &DisplayType::TerminalWithCode(filter))
+            )),
+            Value::from(ok),
+        ],
+    );
     info!("display terminal res = {:?}", res);
 }
 
@@ -435,6 +871,24 @@ pub fn display_floating_window(
         }
     }
 
+    let (message, ok) = match (message, filter) {
+        (Ok(result), OnlyOk) | (Ok(result), Both) => (result.to_string(), true),
+        (Err(result), OnlyErr) | (Err(result), Both) => (result.to_string(), false),
+        _ => return,
+    };
+    let (message, highlights) = ansi_highlights(&message, data);
+    let message = no_output_wrap(&message, data, &DisplayType::TempFloatingWindow(filter));
+    open_floating_window(message, ok, highlights, nvim, data);
+}
+
+/// open a floating window at the end of the code that was run
+fn open_floating_window(
+    message: String,
+    ok: bool,
+    highlights: Value,
+    nvim: &Arc<Mutex<Neovim>>,
+    data: &DataHolder,
+) {
     let col = data
         .current_bloc
         .lines()
@@ -447,33 +901,84 @@ pub fn display_floating_window(
         "trying to open a floating window on row, col = {}, {}",
         row, col
     );
+    let res = call_display(
+        nvim,
+        "fw_open",
+        vec![
+            Value::from(row - 1),
+            Value::from(col),
+            Value::from(message),
+            Value::from(ok),
+            highlights,
+        ],
+    );
+    info!("display floating window res = {:?}", res);
+}
 
-    let res = match (message, filter) {
-        (Ok(result), OnlyOk) | (Ok(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".fw_open({},{},\"{}\", true)",
-            row - 1,
-            col,
-            no_output_wrap(
-                &result.to_string(),
-                data,
-                &DisplayType::TempFloatingWindow(filter)
-            )
-            .replace('\n', "\\\n"),
-        )),
-        (Err(result), OnlyErr) | (Err(result), Both) => nvim.lock().unwrap().command(&format!(
-            "lua require\"sniprun.display\".fw_open({},{},\"{}\", false)",
-            row - 1,
-            col,
-            no_output_wrap(
-                &result.to_string(),
-                data,
-                &DisplayType::TempFloatingWindow(filter)
-            )
-            .replace('\n', "\\\n"),
-        )),
-        _ => Ok(()),
+/// lines of context around the changes of the Diff display
+const DIFF_CONTEXT: usize = 3;
+
+/// show how the output changed since the previous run of the same code
+pub fn display_diff(
+    result: &Result<String, SniprunError>,
+    previous: Option<&str>,
+    nvim: &Arc<Mutex<Neovim>>,
+    data: &DataHolder,
+    filter: DisplayFilter,
+) {
+    let (message, ok) = match (result, filter) {
+        (Ok(result), OnlyOk) | (Ok(result), Both) => (result.to_string(), true),
+        (Err(result), OnlyErr) | (Err(result), Both) => (result.to_string(), false),
+        _ => return,
     };
-    info!("display floating window res = {:?}", res);
+    let current = cleanup(&message, true);
+    let (message, highlights) = match previous {
+        None => (
+            format!("(no previous output)\n{}", current),
+            Value::Array(vec![]),
+        ),
+        Some(previous) if previous == current => {
+            let res = nvim.lock().unwrap().session.call(
+                "nvim_echo",
+                vec![
+                    Value::Array(vec![Value::Array(vec![Value::from("unchanged")])]),
+                    Value::from(false),
+                    Value::Map(vec![]),
+                ],
+            );
+            info!("echo res = {:?}", res);
+            return;
+        }
+        Some(previous) => {
+            let diff = diff::unified(previous, &current, DIFF_CONTEXT);
+            let highlights = diff_highlights(&diff);
+            (diff, highlights)
+        }
+    };
+    open_floating_window(message, ok, highlights, nvim, data);
+}
+
+/// highlights (as [line, start, end, group]) of the added and removed lines of an unified diff
+fn diff_highlights(diff: &str) -> Value {
+    Value::Array(
+        diff.lines()
+            .enumerate()
+            .skip(2) // '--- previous' and '+++ current'
+            .filter_map(|(i, line)| {
+                let group = match line.chars().next() {
+                    Some('+') => "SniprunDiffAdd",
+                    Some('-') => "SniprunDiffDelete",
+                    _ => return None,
+                };
+                Some(Value::Array(vec![
+                    Value::from(i),
+                    Value::from(0),
+                    Value::from(line.len()),
+                    Value::from(group),
+                ]))
+            })
+            .collect(),
+    )
 }
 
 pub fn return_message_classic(
@@ -483,100 +988,215 @@ pub fn return_message_classic(
     data: &DataHolder,
     filter: DisplayFilter,
 ) {
+    // echo (without history) or echomsg
+    let history = *rmt == ReturnMessageType::EchoMsg;
+    let echo = |message: String, hl: Option<&str>| {
+        let mut chunk = vec![Value::from(message)];
+        chunk.extend(hl.map(Value::from));
+        let res = nvim.lock().unwrap().session.call(
+            "nvim_echo",
+            vec![
+                Value::Array(vec![Value::Array(chunk)]),
+                Value::from(history),
+                Value::Map(vec![]),
+            ],
+        );
+        info!("echo res = {:?}", res);
+    };
     match (message, filter) {
         (Ok(answer_ok), OnlyOk) | (Ok(answer_ok), Both) => {
-            //make sure there is no lone "
             let answer_str = no_output_wrap(answer_ok, data, &DisplayType::Classic(filter));
             info!("Final str {}", answer_str);
-
-            match rmt {
-                ReturnMessageType::Multiline => {
-                    let _ = nvim
-                        .lock()
-                        .unwrap()
-                        .command(&format!("echo \"{}\"", answer_str));
-                }
-                ReturnMessageType::EchoMsg => {
-                    let _ = nvim
-                        .lock()
-                        .unwrap()
-                        .command(&format!("echomsg \"{}\"", answer_str));
-                }
-            }
+            echo(answer_str, None);
         }
         (Err(e), OnlyErr) | (Err(e), Both) => match rmt {
             ReturnMessageType::Multiline => {
                 let _ = nvim.lock().unwrap().err_writeln(&format!("{}", e));
             }
-            ReturnMessageType::EchoMsg => {
-                let _ = nvim.lock().unwrap().command(&format!(
-                    "echohl ErrorMsg | echomsg \"{}\" | echohl None",
-                    e
-                ));
-            }
+            ReturnMessageType::EchoMsg => echo(e.to_string(), Some("ErrorMsg")),
         },
         _ => (),
     }
 }
 
-fn shorten_ok(message: &str) -> String {
-    if message.is_empty() {
-        return String::new();
-    }
+/// The line of the output a VirtualText summary shows
+#[derive(Clone, Debug)]
+pub enum SummaryLine {
+    First,
+    Last,
+    /// the first line matching
+    Matching(Regex),
+}
 
-    let mut marker = String::from("<- ");
-    if message.lines().count() > 1 {
-        marker += &".".repeat(message.lines().count().clamp(2, 6));
+impl SummaryLine {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value.as_str()? {
+            "first" => Some(SummaryLine::First),
+            "last" => Some(SummaryLine::Last),
+            regex => match Regex::new(regex) {
+                Ok(regex) => Some(SummaryLine::Matching(regex)),
+                Err(e) => {
+                    info!("[DISPLAY] invalid virtual text line regex: {}", e);
+                    None
+                }
+            },
+        }
     }
+}
 
-    marker.to_string()
-        + message
-            .lines()
-            .filter(|&s| !s.is_empty())
-            .next_back()
-            .unwrap_or("")
+/// How VirtualText summarizes a result on a single line: the `display_options.virtual_text`
+/// settings, overridden by the `virtual_text` option of the interpreter (if any)
+#[derive(Clone, Debug)]
+pub struct VirtualTextFormat {
+    pub prefix: String,
+    pub line: SummaryLine,
+    /// in characters, 0 for no limit
+    pub max_width: usize,
+    ok: bool,
 }
 
-fn shorten_err(message: &str) -> String {
-    if message.is_empty() {
-        return String::new();
+impl VirtualTextFormat {
+    pub fn new(data: &DataHolder, ok: bool) -> Self {
+        let mut format = VirtualTextFormat {
+            prefix: String::from("<- "),
+            line: if ok {
+                SummaryLine::Last
+            } else {
+                SummaryLine::First
+            },
+            max_width: 0,
+            ok,
+        };
+        if let Some(options) = display_option(data, "virtual_text") {
+            format.apply(&options);
+        }
+        if let Some(options) = interpreter_virtual_text_option(data) {
+            format.apply(&options);
+        }
+        format
     }
-    let mut marker = String::from("<- ") + message.lines().next().unwrap_or("");
-    if message.lines().count() > 1 {
-        marker += &".".repeat(message.lines().count().clamp(3, 10));
+
+    fn apply(&mut self, options: &Value) {
+        let options = match options.as_map() {
+            Some(options) => options,
+            None => return,
+        };
+        let get = |key: &str| index_from_name(key, options).map(|i| &options[i].1);
+        if let Some(prefix) = get("prefix").and_then(|v| v.as_str()) {
+            self.prefix = prefix.to_string();
+        }
+        let line_key = if self.ok { "ok_line" } else { "err_line" };
+        if let Some(line) = get(line_key).and_then(SummaryLine::from_value) {
+            self.line = line;
+        }
+        if let Some(max_width) = get("max_width").and_then(|v| v.as_u64()) {
+            self.max_width = max_width as usize;
+        }
     }
-    marker
-}
 
-fn cleanup_and_escape(message: &str, remove_ansi: bool) -> String {
-    let mut escaped = String::with_capacity(message.len());
-    for c in message.chars() {
-        match c {
-            '\x08' => escaped += "\\b",
-            '\x0c' => escaped += "\\f",
-            '\t' => escaped += "\\t",
-            '"' => escaped += "\\\"",
-            '\\' => escaped += "\\\\",
-            c => escaped += &c.to_string(),
+    /// the one-line summary of a (cleaned up) output
+    pub fn format(&self, message: &str) -> String {
+        if message.is_empty() {
+            return String::new();
+        }
+        let line_count = message.lines().count();
+        let first = || message.lines().next();
+        let last = || message.lines().filter(|&l| !l.is_empty()).next_back();
+        let line = match &self.line {
+            SummaryLine::First => first(),
+            SummaryLine::Last => last(),
+            // or the default line, when none matches
+            SummaryLine::Matching(regex) => message
+                .lines()
+                .find(|l| regex.is_match(l))
+                .or_else(|| if self.ok { last() } else { first() }),
         }
+        .unwrap_or("");
+
+        // dots hinting at the other lines: before the line for outputs, after it for errors
+        let (before, after) = match (line_count > 1, self.ok) {
+            (false, _) => (String::new(), String::new()),
+            (true, true) => (".".repeat(line_count.clamp(2, 6)), String::new()),
+            (true, false) => (String::new(), ".".repeat(line_count.clamp(3, 10))),
+        };
+        let markers_width = self.prefix.chars().count() + before.len() + after.len();
+        let line = match self.max_width {
+            0 => line.to_string(),
+            max_width => truncate(line, max_width.saturating_sub(markers_width)),
+        };
+        format!("{}{}{}{}", self.prefix, before, line, after)
     }
+}
 
-    let escaped = if remove_ansi {
-        String::from_utf8(strip_ansi_escapes::strip(escaped.into_bytes())).unwrap()
-    } else {
-        escaped
+/// cut the text to 'width' characters, ending with an ellipsis if it was longer
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// the `virtual_text` option of the interpreter that ran the code
+fn interpreter_virtual_text_option(data: &DataHolder) -> Option<Value> {
+    let config = data.interpreter_options.as_ref()?.as_map()?;
+    let interpreters = config[index_from_name("interpreter_options", config)?]
+        .1
+        .as_map()?;
+    let has_option = |(_, options): &(Value, Value)| {
+        options
+            .as_map()
+            .map_or(false, |o| index_from_name("virtual_text", o).is_some())
     };
+    // not worth selecting the interpreter again otherwise
+    if !interpreters.iter().any(has_option) {
+        return None;
+    }
+    let name = interpreter_name(data)?;
+    let options = interpreters[index_from_name(&name, interpreters)?]
+        .1
+        .as_map()?;
+    Some(options[index_from_name("virtual_text", options)?].1.clone())
+}
 
-    //remove trailing /starting newlines
-    let answer_str = escaped
+/// remove the ANSI escape sequences (if asked to) and the starting/trailing newlines
+fn cleanup(message: &str, remove_ansi: bool) -> String {
+    let message = if remove_ansi {
+        strip_ansi_escapes::strip_str(message)
+    } else {
+        message.to_string()
+    };
+    message
         .trim_start_matches('\n')
         .trim_end_matches('\n')
-        .to_string();
-    answer_str
+        .to_string()
+}
+
+/// with the 'ansi_escape' option, remove the ANSI escape sequences from the message
+/// and get the highlights replacing them
+fn ansi_highlights(message: &str, data: &DataHolder) -> (String, Value) {
+    if !ansi_option(data) {
+        return (message.to_string(), Value::Array(vec![]));
+    }
+    let (plain, mut highlights) = ansi::parse(message);
+    // the starting newlines get removed by no_output_wrap
+    let skipped = plain.len() - plain.trim_start_matches('\n').len();
+    highlights.retain(|h| h.line >= skipped);
+    for h in highlights.iter_mut() {
+        h.line -= skipped;
+    }
+    (
+        plain[skipped..].to_string(),
+        ansi::highlights_value(&highlights),
+    )
 }
 
 fn no_output_wrap(message: &str, data: &DataHolder, current_type: &DisplayType) -> String {
-    let message_clean = cleanup_and_escape(message, ansi_option(data));
+    // terminals render the colors themselves
+    let remove_ansi =
+        ansi_option(data) && !matches!(current_type, Terminal(_) | TerminalWithCode(_));
+    let message_clean = cleanup(message, remove_ansi);
     for dt in data.display_no_output.iter() {
         if dt == current_type && message_clean.is_empty() {
             info!("Empty message converted to 'no output')");
@@ -587,6 +1207,19 @@ fn no_output_wrap(message: &str, data: &DataHolder, current_type: &DisplayType)
     message_clean
 }
 
+/// get a key from the 'display_options' table of the user config
+fn display_option(data: &DataHolder, key: &str) -> Option<Value> {
+    let config = data.interpreter_options.as_ref()?.as_map()?;
+    let display_options = config[index_from_name("display_options", config)?]
+        .1
+        .as_map()?;
+    Some(
+        display_options[index_from_name(key, display_options)?]
+            .1
+            .clone(),
+    )
+}
+
 fn ansi_option(data: &DataHolder) -> bool {
     if let Some(config) = &data.interpreter_options {
         if let Some(ar) = config.as_map() {
@@ -599,3 +1232,142 @@ fn ansi_option(data: &DataHolder) -> bool {
     }
     true
 }
+
+#[cfg(test)]
+mod test_display {
+    use super::*;
+
+    fn rule(entries: Vec<(&str, Value)>) -> Option<DisplayRule> {
+        DisplayRule::from_value(&Value::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (Value::from(k), v))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn rules() {
+        let display = Value::Array(vec![Value::from("TerminalErr")]);
+        let err = rule(vec![
+            ("filetype", Value::from("c")),
+            ("status", Value::from("err")),
+            ("display", display.clone()),
+        ])
+        .unwrap();
+        assert_eq!(err.display, vec![Terminal(OnlyErr)]);
+        let failed: Result<String, SniprunError> =
+            Err(SniprunError::CompilationError("oops".to_string()));
+        assert!(err.matches("c", None, &failed));
+        assert!(!err.matches("c", None, &Ok("ok".to_string())));
+        assert!(!err.matches("rust", None, &failed));
+
+        let long = rule(vec![
+            ("interpreter", Value::from("Python3_fifo")),
+            ("min_lines", Value::from(3)),
+            ("display", display.clone()),
+        ])
+        .unwrap();
+        assert!(long.matches("python", Some("Python3_fifo"), &Ok("1\n2\n3".to_string())));
+        assert!(!long.matches("python", Some("Python3_fifo"), &Ok("1\n2".to_string())));
+        assert!(!long.matches(
+            "python",
+            Some("Python3_original"),
+            &Ok("1\n2\n3".to_string())
+        ));
+
+        assert!(rule(vec![("status", Value::from("maybe")), ("display", display)]).is_none());
+    }
+
+    #[test]
+    fn insert_below_names() {
+        assert_eq!(
+            DisplayType::from_str("CommentOk").unwrap(),
+            InsertBelow(OnlyOk)
+        );
+        assert_eq!(InsertBelow(Both).to_string(), "InsertBelow");
+    }
+
+    #[test]
+    fn virtual_text_format() {
+        let data = DataHolder::default();
+        assert_eq!(
+            VirtualTextFormat::new(&data, true).format("a\nb\n\n"),
+            "<- ...b"
+        );
+        assert_eq!(
+            VirtualTextFormat::new(&data, false).format("error\nat line 2"),
+            "<- error..."
+        );
+
+        let data = DataHolder {
+            interpreter_options: Some(Value::Map(vec![(
+                Value::from("display_options"),
+                Value::Map(vec![(
+                    Value::from("virtual_text"),
+                    Value::Map(vec![
+                        (Value::from("prefix"), Value::from("=> ")),
+                        (Value::from("ok_line"), Value::from("^rows")),
+                        (Value::from("max_width"), Value::from(12)),
+                    ]),
+                )]),
+            )])),
+            ..Default::default()
+        };
+        let format = VirtualTextFormat::new(&data, true);
+        assert_eq!(format.format("a | b\nrows: 42\nend"), "=> ...rows:…");
+        assert_eq!(format.format("no match"), "=> no match");
+    }
+
+    #[test]
+    fn output_limits() {
+        let work_dir = std::env::temp_dir().join("sniprun_test_output_limits");
+        std::fs::create_dir_all(&work_dir).unwrap();
+        let data = DataHolder {
+            work_dir: work_dir.to_string_lossy().into_owned(),
+            run_id: 7,
+            interpreter_options: Some(Value::Map(vec![(
+                Value::from("display_options"),
+                Value::Map(vec![(Value::from("output_max_lines"), Value::from(2))]),
+            )])),
+            ..Default::default()
+        };
+
+        let (result, full_output) = limit_output(Ok("1\n2\n".to_string()), &data);
+        assert_eq!((result, full_output), (Ok("1\n2\n".to_string()), None));
+
+        let (result, full_output) = limit_output(
+            Err(SniprunError::RuntimeError("1\n2\n3\n4".to_string())),
+            &data,
+        );
+        let full_output = full_output.unwrap();
+        assert_eq!(
+            result,
+            Err(SniprunError::RuntimeError(format!(
+                "1\n2\n… 2 more lines, full output in {}",
+                full_output.display()
+            )))
+        );
+        assert_eq!(std::fs::read_to_string(&full_output).unwrap(), "1\n2\n3\n4");
+        assert_eq!(truncated("ééé", 0, 3), Some(("é", 0)));
+    }
+
+    #[test]
+    fn diff_display() {
+        assert_eq!(DisplayType::from_str("DiffOk").unwrap(), Diff(OnlyOk));
+        let highlights = diff_highlights(&diff::unified("1\n2", "1\n3", 1));
+        let groups: Vec<(u64, &str)> = highlights
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| {
+                let h = h.as_array().unwrap();
+                (h[0].as_u64().unwrap(), h[3].as_str().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![(4, "SniprunDiffDelete"), (5, "SniprunDiffAdd")]
+        );
+    }
+}
@@ -47,4 +47,74 @@ pub enum SniprunError {
     /// code blocs are to be run from 1 'sniprun' command
     #[error("")]
     ReRunRanges(Vec<(usize, usize)>),
+
+    /// raised when the executable (compiler, interpreter...) needed to run the code is not installed
+    #[error("{}", missing_executable_message(.executable, .option, .interpreter, .alternatives))]
+    MissingExecutable {
+        executable: String,
+        /// the interpreter option that selects the executable, if any
+        option: Option<String>,
+        interpreter: String,
+        /// the other interpreters available for the filetype
+        alternatives: Vec<String>,
+    },
+}
+
+fn missing_executable_message(
+    executable: &str,
+    option: &Option<String>,
+    interpreter: &str,
+    alternatives: &[String],
+) -> String {
+    let mut message = format!(
+        "Executable not found: '{}'\n - install it (in neovim's PATH)",
+        executable
+    );
+    if let Some(option) = option {
+        message += &format!(
+            "\n - or set the '{}' option of {} (in interpreter_options) to another one",
+            option, interpreter
+        );
+    }
+    if !alternatives.is_empty() {
+        message += &format!(
+            "\n - or use another interpreter (with selected_interpreters): {}",
+            alternatives.join(", ")
+        );
+    }
+    message
+}
+
+impl SniprunError {
+    /// name of the variant, for the API display
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SniprunError::UnknownError(_) => "UnknownError",
+            SniprunError::InternalError(_) => "InternalError",
+            SniprunError::FetchCodeError => "FetchCodeError",
+            SniprunError::InterpreterLimitationError(_) => "InterpreterLimitationError",
+            SniprunError::InterpreterError => "InterpreterError",
+            SniprunError::UnsufficientSupportLevel => "UnsufficientSupportLevel",
+            SniprunError::CompilationError(_) => "CompilationError",
+            SniprunError::RuntimeError(_) => "RuntimeError",
+            SniprunError::CustomError(_) => "CustomError",
+            SniprunError::ReRunRanges(_) => "ReRunRanges",
+            SniprunError::MissingExecutable { .. } => "MissingExecutable",
+        }
+    }
+
+    /// the same error, with its message (if it has one) transformed by 'f'
+    pub fn map_message(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            SniprunError::UnknownError(m) => SniprunError::UnknownError(f(m)),
+            SniprunError::InternalError(m) => SniprunError::InternalError(f(m)),
+            SniprunError::InterpreterLimitationError(m) => {
+                SniprunError::InterpreterLimitationError(f(m))
+            }
+            SniprunError::CompilationError(m) => SniprunError::CompilationError(f(m)),
+            SniprunError::RuntimeError(m) => SniprunError::RuntimeError(f(m)),
+            SniprunError::CustomError(m) => SniprunError::CustomError(f(m)),
+            other => other,
+        }
+    }
 }
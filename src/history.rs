@@ -0,0 +1,203 @@
+//! History of the runs
+//!
+//! Every run (its code, where it comes from, the start of its result...) is appended to a
+//! JSON lines file in the work directory, keeping only the most recent ones.
+//! `:SnipHistory` lists them.
+
+use crate::display::capped_output;
+use crate::error::SniprunError;
+use crate::interpreter::index_from_name;
+use crate::launcher::Launcher;
+use crate::DataHolder;
+use log::info;
+use neovim_lib::{Neovim, Value};
+use std::fs::{File, OpenOptions};
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use ureq::serde_json;
+use ureq::serde_json::{json, Value as JsonValue};
+
+const HISTORY_FILE: &str = "history.jsonl";
+const DEFAULT_MAX_ENTRIES: usize = 500;
+/// only the start of the results is kept, for the history file to stay small
+/// (it's rewritten at each run, and sent whole to neovim by `:SnipHistory`)
+const RESULT_MAX_LINES: usize = 100;
+const RESULT_MAX_BYTES: usize = 10_000;
+
+/// runs finishing at the same time must not write the file concurrently
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// get a key from the 'history' table of the user config
+fn history_option(data: &DataHolder, key: &str) -> Option<Value> {
+    let config = data.interpreter_options.as_ref()?.as_map()?;
+    let history = config[index_from_name("history", config)?].1.as_map()?;
+    Some(history[index_from_name(key, history)?].1.clone())
+}
+
+fn history_file(data: &DataHolder) -> PathBuf {
+    PathBuf::from(&data.work_dir).join(HISTORY_FILE)
+}
+
+/// exclusive lock on the history, shared with the other neovim instances
+/// (released when the returned file is closed)
+fn lock_history(data: &DataHolder) -> Option<File> {
+    let lock = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(history_file(data).with_extension("lock"))
+        .ok()?;
+    match unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) } {
+        0 => Some(lock),
+        _ => None,
+    }
+}
+
+fn enabled(data: &DataHolder) -> bool {
+    history_option(data, "enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
+
+fn max_entries(data: &DataHolder) -> usize {
+    history_option(data, "max_entries")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_MAX_ENTRIES, |n| n as usize)
+}
+
+/// the entries, oldest first
+pub fn entries(data: &DataHolder) -> Vec<JsonValue> {
+    std::fs::read_to_string(history_file(data))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+/// append the run to the history (unless disabled), keeping only the 'max_entries' last ones
+pub fn record(result: &Result<String, SniprunError>, data: &DataHolder) {
+    if !enabled(data) {
+        return;
+    }
+    if let Err(SniprunError::ReRunRanges(_)) = result {
+        return; // the ranges are recorded when run
+    }
+    let code = if data.current_bloc.trim().is_empty() {
+        &data.current_line
+    } else {
+        &data.current_bloc
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let entry = json!({
+        "timestamp": timestamp,
+        "file": data.filepath,
+        "range": data.range,
+        "filetype": data.filetype,
+        "interpreter": Launcher::new(data.clone()).select().map(|(name, _)| name),
+        "code": code,
+        "result": match result {
+            Ok(message) => capped_output(message, RESULT_MAX_LINES, RESULT_MAX_BYTES),
+            Err(e) => capped_output(&e.to_string(), RESULT_MAX_LINES, RESULT_MAX_BYTES),
+        },
+        "status": if result.is_ok() { "ok" } else { "error" },
+        "duration": data.run_duration.map(|duration| duration.as_millis() as u64),
+    });
+
+    let _lock = HISTORY_LOCK.lock().unwrap();
+    let _file_lock = lock_history(data);
+    let mut lines: Vec<String> = std::fs::read_to_string(history_file(data))
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect();
+    lines.push(entry.to_string());
+    let skip = lines.len().saturating_sub(max_entries(data));
+    // replaced at once, never seen half-written
+    let tmp = history_file(data).with_extension("jsonl.tmp");
+    let res = std::fs::write(&tmp, lines[skip..].join("\n") + "\n")
+        .and_then(|_| std::fs::rename(&tmp, history_file(data)));
+    info!("[HISTORY] recorded run: {:?}", res);
+}
+
+pub fn clear(data: &DataHolder) {
+    let _lock = HISTORY_LOCK.lock().unwrap();
+    let _file_lock = lock_history(data);
+    let _ = std::fs::remove_file(history_file(data));
+}
+
+fn to_value(json: &JsonValue) -> Value {
+    match json {
+        JsonValue::Null => Value::Nil,
+        JsonValue::Bool(b) => Value::from(*b),
+        JsonValue::Number(n) => n
+            .as_i64()
+            .map(Value::from)
+            .unwrap_or_else(|| Value::from(n.as_f64().unwrap_or(0.0))),
+        JsonValue::String(s) => Value::from(s.as_str()),
+        JsonValue::Array(a) => Value::Array(a.iter().map(to_value).collect()),
+        JsonValue::Object(o) => Value::Map(
+            o.iter()
+                .map(|(k, v)| (Value::from(k.as_str()), to_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// open the history picker, with the most recent runs first
+pub fn show(data: &DataHolder, nvim: &Arc<Mutex<Neovim>>) {
+    let entries = entries(data).iter().rev().map(to_value).collect();
+    let res = nvim.lock().unwrap().session.call(
+        "nvim_exec_lua",
+        vec![
+            Value::from("require'sniprun.history'.pick(...)"),
+            Value::Array(vec![Value::Array(entries)]),
+        ],
+    );
+    info!("[HISTORY] show history: {:?}", res);
+}
+
+#[cfg(test)]
+mod test_history {
+    use super::*;
+
+    #[test]
+    fn bounded() {
+        let work_dir = std::env::temp_dir().join("sniprun_test_history");
+        std::fs::create_dir_all(&work_dir).unwrap();
+        let mut data = DataHolder {
+            work_dir: work_dir.to_string_lossy().into_owned(),
+            current_bloc: "print(1)".to_string(),
+            ..Default::default()
+        };
+        clear(&data);
+        for i in 0..3 {
+            record(&Ok(i.to_string()), &data);
+        }
+        record(&Err(SniprunError::RuntimeError("oops".to_string())), &data);
+        let all = entries(&data);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[3]["status"], "error");
+        assert_eq!(all[3]["code"], "print(1)");
+
+        data.interpreter_options = Some(Value::Map(vec![(
+            Value::from("history"),
+            Value::Map(vec![(Value::from("max_entries"), Value::from(2))]),
+        )]));
+        record(&Ok("last".to_string()), &data);
+        let all = entries(&data);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1]["result"], "last");
+
+        // only the start of the outputs is kept
+        record(&Ok("line\n".repeat(6000)), &data);
+        let all = entries(&data);
+        let result = all[1]["result"].as_str().unwrap();
+        assert_eq!(result.lines().count(), RESULT_MAX_LINES + 1);
+        assert!(result.ends_with("\n… 5900 more lines"));
+        clear(&data);
+    }
+}
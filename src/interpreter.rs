@@ -1,7 +1,12 @@
 use crate::error::SniprunError;
+use crate::launcher::Launcher;
 use crate::DataHolder;
 use log::info;
+use neovim_lib::NeovimApi;
 use std::fmt::Display;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::{Child, Command, Output};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[allow(dead_code)]
@@ -180,6 +185,11 @@ pub trait Interpreter: ReplLikeInterpreter {
         }
     }
 }
+/// prefix of the output lines that report an image captured during the run
+pub const IMAGE_MARKER: &str = "[image] ";
+/// the image directories of that many runs are kept, for the image viewers & plugins
+const MAX_IMAGE_RUNS: usize = 20;
+
 #[derive(Debug, PartialEq)]
 pub enum ErrTruncate {
     Short,
@@ -200,8 +210,61 @@ pub trait InterpreterUtils {
     fn error_truncate(data: &DataHolder) -> ErrTruncate;
 
     fn get_repl_timeout(data: &DataHolder) -> u64;
+    fn get_image_dir(data: &DataHolder) -> Option<String>;
+    fn get_buffer_content(data: &DataHolder) -> String;
+    fn report_images(image_dir: &Option<String>) -> String;
+    fn string_literal(s: &str) -> String;
     fn get_compiler_or(data: &DataHolder, or: &str) -> String;
     fn get_interpreter_or(data: &DataHolder, or: &str) -> String;
+
+    fn run_command(
+        data: &DataHolder,
+        command: &mut Command,
+        option: Option<&str>,
+    ) -> Result<Output, SniprunError>;
+    fn spawn_command(
+        data: &DataHolder,
+        command: &mut Command,
+        option: Option<&str>,
+    ) -> Result<Child, SniprunError>;
+    fn check_executable(
+        data: &DataHolder,
+        command: &str,
+        option: Option<&str>,
+    ) -> Result<(), SniprunError>;
+}
+
+/// the error of a command that could not be started
+fn launch_error<T: Interpreter>(
+    data: &DataHolder,
+    command: &Command,
+    option: Option<&str>,
+    error: std::io::Error,
+) -> SniprunError {
+    let executable = command.get_program().to_string_lossy().into_owned();
+    info!("could not start '{}': {}", executable, error);
+    if error.kind() != ErrorKind::NotFound {
+        return SniprunError::InternalError(format!("Unable to start '{}': {}", executable, error));
+    }
+    missing_executable::<T>(data, executable, option)
+}
+
+/// the error of an executable that could not be found
+fn missing_executable<T: Interpreter>(
+    data: &DataHolder,
+    executable: String,
+    option: Option<&str>,
+) -> SniprunError {
+    SniprunError::MissingExecutable {
+        executable,
+        option: option.map(String::from),
+        interpreter: T::get_name(),
+        alternatives: Launcher::new(data.clone())
+            .interpreters_for_filetype()
+            .into_iter()
+            .filter(|name| *name != T::get_name())
+            .collect(),
+    }
 }
 
 impl<T: Interpreter> InterpreterUtils for T {
@@ -239,7 +302,8 @@ impl<T: Interpreter> InterpreterUtils for T {
                 data.interpreter_data.unwrap().lock().unwrap().content =
                     previous_code + "\n" + &code;
             }
-            info!("code saved: {}", self.read_previous_code());
+            // the content may hold secrets (ex: Http_original's responses), keep it out of the log
+            info!("code saved: {} bytes", self.read_previous_code().len());
         }
     }
 
@@ -314,6 +378,92 @@ impl<T: Interpreter> InterpreterUtils for T {
         }
     }
 
+    /// returns a new, per-run directory to save the images (plots, figures) produced by the
+    /// snippet into, if the "image_capture" option is enabled for this interpreter: the code
+    /// saving the images creates it, only when there is some. The oldest ones are removed.
+    fn get_image_dir(data: &DataHolder) -> Option<String> {
+        let enabled = T::get_interpreter_option(data, "image_capture")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let images_dir = Path::new(&data.work_dir).join("images");
+        let mut runs: Vec<(std::time::SystemTime, std::path::PathBuf)> =
+            match std::fs::read_dir(&images_dir) {
+                Ok(entries) => entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+                    .filter(|(_, path)| path.is_dir())
+                    .collect(),
+                Err(_) => vec![],
+            };
+        runs.sort();
+        let old_runs = runs.len().saturating_sub(MAX_IMAGE_RUNS - 1);
+        for (_, path) in runs.iter().take(old_runs) {
+            if let Err(e) = std::fs::remove_dir_all(path) {
+                info!("could not remove image directory {}: {}", path.display(), e);
+            }
+        }
+
+        let run_id = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let image_dir = format!("{}/{}_{}", images_dir.display(), T::get_name(), run_id);
+        info!("capturing images to {}", image_dir);
+        Some(image_dir)
+    }
+
+    /// the whole current buffer (eg: for file-level settings outside of the snippet),
+    /// read from the file if neovim is not available
+    fn get_buffer_content(data: &DataHolder) -> String {
+        if let Some(nvim_instance) = data.nvim_instance.clone() {
+            let mut nvim = nvim_instance.lock().unwrap();
+            if let Ok(buffer) = nvim.get_current_buf() {
+                if let Ok(lines) = buffer.get_lines(&mut nvim, 0, -1, false) {
+                    return lines.join("\n");
+                }
+            }
+        }
+        std::fs::read_to_string(&data.filepath).unwrap_or_default()
+    }
+
+    /// list the images saved in the image directory, one per line, prefixed with IMAGE_MARKER
+    fn report_images(image_dir: &Option<String>) -> String {
+        let mut images: Vec<String> = match image_dir.as_ref().map(std::fs::read_dir) {
+            Some(Ok(entries)) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path().display().to_string())
+                .collect(),
+            _ => return String::new(),
+        };
+        images.sort();
+        images
+            .iter()
+            .map(|path| String::from("\n") + IMAGE_MARKER + path)
+            .collect()
+    }
+
+    /// a double-quoted string literal of s (ex: a path) to paste in generated code,
+    /// that python, julia and R all read back as s: no interpolation, every special
+    /// character escaped
+    fn string_literal(s: &str) -> String {
+        let mut literal = String::from("\"");
+        for c in s.chars() {
+            match c {
+                '\\' => literal.push_str("\\\\"),
+                '"' => literal.push_str("\\\""),
+                // '$' interpolates in julia, "\$" is invalid in R
+                '$' => literal.push_str("\\x24"),
+                c if c.is_ascii_control() => literal.push_str(&format!("\\x{:02x}", c as u8)),
+                c => literal.push(c),
+            }
+        }
+        literal.push('"');
+        literal
+    }
+
     fn get_compiler_or(data: &DataHolder, or: &str) -> String {
         if let Some(compiler) = T::get_interpreter_option(data, "compiler") {
             if let Some(compiler_valid_str) = compiler.as_str() {
@@ -335,6 +485,57 @@ impl<T: Interpreter> InterpreterUtils for T {
         or.to_string()
     }
 
+    /// Run a command to completion. A missing executable is reported with the interpreter
+    /// option ("compiler", "interpreter") that selects it, and the other interpreters
+    /// available for the filetype
+    fn run_command(
+        data: &DataHolder,
+        command: &mut Command,
+        option: Option<&str>,
+    ) -> Result<Output, SniprunError> {
+        command
+            .output()
+            .map_err(|e| launch_error::<T>(data, command, option, e))
+    }
+
+    /// Start a command in the background, its errors being reported like run_command's
+    fn spawn_command(
+        data: &DataHolder,
+        command: &mut Command,
+        option: Option<&str>,
+    ) -> Result<Child, SniprunError> {
+        command
+            .spawn()
+            .map_err(|e| launch_error::<T>(data, command, option, e))
+    }
+
+    /// Check that the executable of a command line (its first word) can be found, for the
+    /// REPLs launched in the background: they would only time out otherwise
+    fn check_executable(
+        data: &DataHolder,
+        command: &str,
+        option: Option<&str>,
+    ) -> Result<(), SniprunError> {
+        let executable = command.split_whitespace().next().unwrap_or_default();
+        let found = if executable.contains('/') {
+            Path::new(executable).is_file()
+        } else {
+            std::env::var_os("PATH").map_or(false, |paths| {
+                std::env::split_paths(&paths).any(|dir| dir.join(executable).is_file())
+            })
+        };
+        if found {
+            Ok(())
+        } else {
+            info!("could not find '{}'", executable);
+            Err(missing_executable::<T>(
+                data,
+                executable.to_string(),
+                option,
+            ))
+        }
+    }
+
     fn error_truncate(data: &DataHolder) -> ErrTruncate {
         if let Some(error_truncate) = T::get_interpreter_option(data, "error_truncate") {
             if let Some(error_truncate) = error_truncate.as_str() {
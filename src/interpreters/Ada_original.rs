@@ -107,17 +107,19 @@ impl Interpreter for Ada_original {
             .expect("Unable to write to file for language_subname");
 
         let compiler = Ada_original::get_compiler_or(&self.data, "gnatmake");
-        let output = Command::new(compiler.split_whitespace().next().unwrap())
-            .args(compiler.split_whitespace().skip(1))
-            .arg("main")
-            .arg(&self.main_file_path)
-            .current_dir(&self.ada_work_dir)
-            .output()
-            .expect("Unable to start process");
+        let output = Ada_original::run_command(
+            &self.data,
+            Command::new(compiler.split_whitespace().next().unwrap())
+                .args(compiler.split_whitespace().skip(1))
+                .arg("main")
+                .arg(&self.main_file_path)
+                .current_dir(&self.ada_work_dir),
+            Some("compiler"),
+        )?;
         if !output.status.success() {
-            return Err(SniprunError::CompilationError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::CompilationError(decode_output(
+                &output.stderr,
+            )));
         }
 
         Ok(())
@@ -130,12 +132,10 @@ impl Interpreter for Ada_original {
 
         if output.status.success() {
             //return stdout
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else {
             // return stderr
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
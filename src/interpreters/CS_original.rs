@@ -94,28 +94,27 @@ impl Interpreter for CS_original {
 
     fn execute(&mut self) -> Result<String, SniprunError> {
         let interpreter = CS_original::get_interpreter_or(&self.data, "coffee");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = CS_original::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         info!("yay from cs interpreter");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if CS_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ));
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::RuntimeError(decode_output(&output.stderr)));
         }
     }
 }
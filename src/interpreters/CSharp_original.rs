@@ -130,16 +130,18 @@ impl Interpreter for CSharp_original {
             .expect("Unable to write to file for csharp-original");
 
         //compile it (to the bin_path that arleady points to the rigth path)
-        let output = Command::new(self.compiler.split_whitespace().next().unwrap())
-            .args(self.compiler.split_whitespace().skip(1))
-            .arg(String::from("-out:") + &self.bin_path)
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = CSharp_original::run_command(
+            &self.data,
+            Command::new(self.compiler.split_whitespace().next().unwrap())
+                .args(self.compiler.split_whitespace().skip(1))
+                .arg(String::from("-out:") + &self.bin_path)
+                .arg(&self.main_file_path),
+            Some("compiler"),
+        )?;
 
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            let error_message = String::from_utf8(output.stderr).unwrap();
+            let error_message = decode_output(&output.stderr);
             //take first line and remove first 'error' word (redondant)
             let first_line = error_message
                 .lines()
@@ -155,26 +157,25 @@ impl Interpreter for CSharp_original {
 
     fn execute(&mut self) -> Result<String, SniprunError> {
         //run th binary and get the std output (or stderr)
-        let output = Command::new("mono")
-            .arg(&self.bin_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = CSharp_original::run_command(
+            &self.data,
+            Command::new("mono")
+                .arg(&self.bin_path)
+                .args(&self.get_data().cli_args),
+            None,
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if CSharp_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ));
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::RuntimeError(decode_output(&output.stderr)));
         }
     }
 }
@@ -195,11 +195,11 @@ impl Interpreter for C_original {
             format!("{:?}", cmd).replace('\"', "")
         );
 
-        let output = cmd.output().expect("Unable to start process");
+        let output = C_original::run_command(&self.data, cmd, Some("compiler"))?;
 
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            let error_message = String::from_utf8(output.stderr).unwrap();
+            let error_message = decode_output(&output.stderr);
             info!("Full GCC error message: {}", error_message);
             let mut relevant_error = String::new();
 
@@ -223,7 +223,7 @@ impl Interpreter for C_original {
 
             Err(SniprunError::CompilationError(relevant_error))
         } else {
-            let compiler_output = String::from_utf8(output.stdout).unwrap();
+            let compiler_output = decode_output(&output.stdout);
             info!("compiler output:\n{}\n", compiler_output);
             Ok(())
         }
@@ -235,20 +235,17 @@ impl Interpreter for C_original {
             .output()
             .expect("Unable to start process");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if C_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ));
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::RuntimeError(decode_output(&output.stderr)));
         }
     }
 }
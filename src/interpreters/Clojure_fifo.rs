@@ -53,7 +53,7 @@ impl Clojure_fifo {
             if let Ok(mut file) = std::fs::File::open(&err_path) {
                 info!("errfile exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 if res.is_ok() {
                     info!("errfile could be read : {:?}", err_contents);
                     // info!("file : {:?}", contents);
@@ -81,7 +81,7 @@ impl Clojure_fifo {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -216,18 +216,19 @@ impl Interpreter for Clojure_fifo {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new(self.interpreter.split_whitespace().next().unwrap())
-            .args(self.interpreter.split_whitespace().skip(1))
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = Clojure_fifo::run_command(
+            &self.data,
+            Command::new(self.interpreter.split_whitespace().next().unwrap())
+                .args(self.interpreter.split_whitespace().skip(1))
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Clojure_fifo::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr)
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .filter(|l| !l.to_lowercase().contains("warning"))
                     .take(2)
@@ -235,9 +236,7 @@ impl Interpreter for Clojure_fifo {
                     .join("\n"),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -274,6 +273,11 @@ impl ReplLikeInterpreter for Clojure_fifo {
                 init_repl_cmd, &self.cache_dir
             );
 
+            Clojure_fifo::check_executable(
+                &self.data,
+                &self.interpreter_repl,
+                Some("interpreter_repl"),
+            )?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
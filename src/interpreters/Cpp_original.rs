@@ -155,19 +155,19 @@ impl Interpreter for Cpp_original {
         let mut _file =
             File::create(&self.main_file_path).expect("Failed to create file for rust-original");
         write(&self.main_file_path, &self.code).expect("Unable to write to file for rust-original");
-        let output = Command::new(self.compiler.split_whitespace().next().unwrap())
-            .args(self.compiler.split_whitespace().skip(1))
-            .arg(&self.main_file_path)
-            .arg("-o")
-            .arg(&self.bin_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Cpp_original::run_command(
+            &self.data,
+            Command::new(self.compiler.split_whitespace().next().unwrap())
+                .args(self.compiler.split_whitespace().skip(1))
+                .arg(&self.main_file_path)
+                .arg("-o")
+                .arg(&self.bin_path),
+            Some("compiler"),
+        )?;
 
         if !output.status.success() {
             if Cpp_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
-                let error_pos = String::from_utf8(output.stderr.clone())
-                    .unwrap()
-                    .find("error:");
+                let error_pos = decode_output(&output.stderr).find("error:");
                 if let Some(index) = error_pos {
                     let err = output.stderr.split_at(index + 6).1;
                     Err(SniprunError::CompilationError(
@@ -175,8 +175,7 @@ impl Interpreter for Cpp_original {
                     ))
                 } else {
                     Err(SniprunError::CompilationError(
-                        String::from_utf8(output.stderr.clone())
-                            .unwrap()
+                        decode_output(&output.stderr)
                             .lines()
                             .take(5)
                             .collect::<Vec<&str>>()
@@ -184,9 +183,9 @@ impl Interpreter for Cpp_original {
                     ))
                 }
             } else {
-                Err(SniprunError::CompilationError(
-                    String::from_utf8(output.stderr.clone()).unwrap(),
-                ))
+                Err(SniprunError::CompilationError(decode_output(
+                    &output.stderr,
+                )))
             }
         } else {
             Ok(())
@@ -199,11 +198,9 @@ impl Interpreter for Cpp_original {
             .output()
             .expect("Unable to start process");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -93,27 +93,26 @@ impl Interpreter for D_original {
     fn execute(&mut self) -> Result<String, SniprunError> {
         //run th binary and get the std output (or stderr)
         let interpreter = D_original::get_interpreter_or(&self.data, "dmd");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg("-run")
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = D_original::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg("-run")
+                .arg(&self.main_file_path),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if D_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -26,13 +26,18 @@ impl Elixir_original {
         let mut contents = String::new();
 
         let mut pause = std::time::Duration::from_millis(50);
+        let start = std::time::Instant::now();
         loop {
             std::thread::sleep(pause);
             pause = pause.saturating_add(std::time::Duration::from_millis(50));
 
+            if start.elapsed().as_secs() > Elixir_original::get_repl_timeout(&self.data) {
+                return Err(String::from("reached the repl timeout"));
+            }
+
             if let Ok(mut file) = std::fs::File::open(&path) {
                 info!("file exists");
-                let res = file.read_to_string(&mut contents);
+                let res = read_output(&mut file, &mut contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", contents);
                     // info!("file : {:?}", contents);
@@ -168,27 +173,26 @@ impl Interpreter for Elixir_original {
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
         let interpreter = Elixir_original::get_interpreter_or(&self.data, "elixir");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = Elixir_original::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Elixir_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -221,6 +225,7 @@ impl ReplLikeInterpreter for Elixir_original {
                 init_repl_cmd, &self.cache_dir
             );
 
+            Elixir_original::check_executable(&self.data, "iex", None)?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
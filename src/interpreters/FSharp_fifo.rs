@@ -49,7 +49,7 @@ impl FSharp_fifo {
             if let Ok(mut file) = std::fs::File::open(&err_path) {
                 info!("errfile exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 if res.is_ok() {
                     info!("errfile could be read : {:?}", err_contents);
                     // info!("file : {:?}", contents);
@@ -79,7 +79,7 @@ impl FSharp_fifo {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -198,28 +198,27 @@ impl Interpreter for FSharp_fifo {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new(self.interpreter.split_whitespace().next().unwrap())
-            .args(self.interpreter.split_whitespace().skip(1))
-            .arg(&self.interpreter)
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = FSharp_fifo::run_command(
+            &self.data,
+            Command::new(self.interpreter.split_whitespace().next().unwrap())
+                .args(self.interpreter.split_whitespace().skip(1))
+                .arg(&self.interpreter)
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if FSharp_fifo::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -256,6 +255,7 @@ impl ReplLikeInterpreter for FSharp_fifo {
                 init_repl_cmd, &self.cache_dir
             );
 
+            FSharp_fifo::check_executable(&self.data, &self.interpreter, Some("interpreter"))?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
@@ -317,12 +317,14 @@ impl Interpreter for Generic {
             write(&self.main_file_path, &self.code).expect("Unable to write to file for generic");
             info!("compiling main file to exe");
 
-            let output = Command::new(self.compiler.split_whitespace().next().unwrap())
-                .args(self.compiler.split_whitespace().skip(1))
-                .arg(&self.main_file_path)
-                .current_dir(&self.workdir)
-                .output()
-                .expect("Unable to execute compiler specified in Generic's config");
+            let output = Generic::run_command(
+                &self.data,
+                Command::new(self.compiler.split_whitespace().next().unwrap())
+                    .args(self.compiler.split_whitespace().skip(1))
+                    .arg(&self.main_file_path)
+                    .current_dir(&self.workdir),
+                Some("compiler"),
+            )?;
 
             info!(
                 "generic compiled, status.success?:{}",
@@ -332,17 +334,16 @@ impl Interpreter for Generic {
                 return Ok(());
             } else if Generic::error_truncate(&self.get_data()) == ErrTruncate::Short {
                 return Err(SniprunError::CompilationError(
-                    String::from_utf8(output.stderr.clone())
-                        .unwrap()
+                    decode_output(&output.stderr)
                         .lines()
                         .last()
-                        .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                        .unwrap_or(&decode_output(&output.stderr))
                         .to_owned(),
                 ));
             } else {
-                return Err(SniprunError::CompilationError(
-                    String::from_utf8(output.stderr).unwrap(),
-                ));
+                return Err(SniprunError::CompilationError(decode_output(
+                    &output.stderr,
+                )));
             }
         }
         Ok(())
@@ -350,13 +351,15 @@ impl Interpreter for Generic {
 
     fn execute(&mut self) -> Result<String, SniprunError> {
         let output = if self.interpreted_lang {
-            Command::new(self.interpreter.split_whitespace().next().unwrap())
-                .args(self.interpreter.split_whitespace().skip(1))
-                .arg(&self.main_file_path)
-                .args(&self.get_data().cli_args)
-                .current_dir(&self.workdir)
-                .output()
-                .expect("Unable to start process specified in Generic's config")
+            Generic::run_command(
+                &self.data,
+                Command::new(self.interpreter.split_whitespace().next().unwrap())
+                    .args(self.interpreter.split_whitespace().skip(1))
+                    .arg(&self.main_file_path)
+                    .args(&self.get_data().cli_args)
+                    .current_dir(&self.workdir),
+                Some("interpreter"),
+            )?
         } else {
             Command::new(self.exe_path.clone())
                 .args(&self.get_data().cli_args)
@@ -369,20 +372,17 @@ impl Interpreter for Generic {
             output.status.success()
         );
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Generic::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
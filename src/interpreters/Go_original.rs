@@ -242,30 +242,31 @@ impl Interpreter for Go_original {
         write(&self.main_file_path, &self.code).expect("Unable to write to file for go-original");
 
         //compile it (to the bin_path that arleady points to the rigth path)
-        let output = Command::new(self.compiler.split_whitespace().next().unwrap())
-            .args(self.compiler.split_whitespace().skip(1))
-            .arg("build")
-            .arg("-o")
-            .arg(&self.go_work_dir)
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Go_original::run_command(
+            &self.data,
+            Command::new(self.compiler.split_whitespace().next().unwrap())
+                .args(self.compiler.split_whitespace().skip(1))
+                .arg("build")
+                .arg("-o")
+                .arg(&self.go_work_dir)
+                .arg(&self.main_file_path),
+            Some("compiler"),
+        )?;
 
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
             if Go_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
                 Err(SniprunError::CompilationError(
-                    String::from_utf8(output.stderr.clone())
-                        .unwrap()
+                    decode_output(&output.stderr)
                         .lines()
                         .last()
-                        .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                        .unwrap_or(&decode_output(&output.stderr))
                         .to_owned(),
                 ))
             } else {
-                Err(SniprunError::CompilationError(
-                    String::from_utf8(output.stderr).unwrap(),
-                ))
+                Err(SniprunError::CompilationError(decode_output(
+                    &output.stderr,
+                )))
             }
         } else {
             Ok(())
@@ -279,20 +280,17 @@ impl Interpreter for Go_original {
             .output()
             .expect("Unable to start process");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Go_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -108,21 +108,23 @@ impl Interpreter for Haskell_original {
             &self.main_file_path, &self.bin_path
         );
         let compiler = Haskell_original::get_compiler_or(&self.data, "ghc");
-        let output = Command::new(compiler.split_whitespace().next().unwrap())
-            .args(compiler.split_whitespace().skip(1))
-            .arg("-dynamic")
-            .arg("-o")
-            .arg(self.bin_path.clone())
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Haskell_original::run_command(
+            &self.data,
+            Command::new(compiler.split_whitespace().next().unwrap())
+                .args(compiler.split_whitespace().skip(1))
+                .arg("-dynamic")
+                .arg("-o")
+                .arg(self.bin_path.clone())
+                .arg(&self.main_file_path),
+            Some("compiler"),
+        )?;
 
         info!("code : {:?}", &self.code);
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            Err(SniprunError::CompilationError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::CompilationError(decode_output(
+                &output.stderr,
+            )))
         } else {
             Ok(())
         }
@@ -135,20 +137,17 @@ impl Interpreter for Haskell_original {
             .output()
             .expect("Unable to start process");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Haskell_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -1,7 +1,329 @@
 use crate::interpreters::import::*;
 
+use base64::Engine;
 use http_rest_file::model::{FileParseResult, HttpMethod, RequestTarget, WithDefault};
+use std::collections::HashMap;
 use std::io::Cursor;
+use std::sync::Mutex;
+use ureq::serde_json;
+use ureq::serde_json::Value as JsonValue;
+
+/// environments file, holding one object of variables per environment
+const ENV_FILE: &str = "http-client.env.json";
+/// same as ENV_FILE, but meant to be kept out of version control (for secrets)
+const PRIVATE_ENV_FILE: &str = "http-client.private.env.json";
+/// variables shared by all environments in ENV_FILE
+const SHARED_ENV: &str = "$shared";
+
+/// agent (connection pool & cookie jar) shared by the requests of the neovim session
+static AGENT: Mutex<Option<ureq::Agent>> = Mutex::new(None);
+/// cookies received during the session, None until loaded (see the 'save_cookies' option)
+static COOKIE_JAR: Mutex<Option<Vec<StoredCookie>>> = Mutex::new(None);
+
+/// a request ready to be sent: variables and references to previous responses resolved
+struct PreparedRequest {
+    name: Option<String>,
+    method: HttpMethod,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: String,
+    no_cookie_jar: bool,
+    graphql: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct StoredCookie {
+    domain: String,
+    path: String,
+    name: String,
+    value: String,
+    /// without a Domain attribute, the cookie is only sent back to the exact same host
+    host_only: bool,
+    /// only sent over https
+    secure: bool,
+    /// unix timestamp, None for session cookies
+    expires: Option<u64>,
+}
+
+/// seconds since the unix epoch
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// parse a cookie date (RFC 6265 §5.1.1), ex: 'Wed, 21 Oct 2015 07:28:00 GMT'
+fn parse_cookie_date(date: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let (mut time, mut day, mut month, mut year) = (None, None, None, None);
+    let is_number = |s: &str, digits: std::ops::RangeInclusive<usize>| {
+        digits.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit())
+    };
+    for token in date
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+        .filter(|t| !t.is_empty())
+    {
+        let hms: Vec<&str> = token.split(':').collect();
+        if time.is_none() && hms.len() == 3 && hms.iter().all(|n| is_number(n, 1..=2)) {
+            let n: Vec<u64> = hms.iter().map(|n| n.parse().unwrap()).collect();
+            time = Some(n[0] * 3600 + n[1] * 60 + n[2]);
+        } else if day.is_none() && is_number(token, 1..=2) {
+            day = token.parse::<u64>().ok();
+        } else if month.is_none() && token.len() >= 3 && token.is_char_boundary(3) {
+            month = MONTHS
+                .iter()
+                .position(|m| token[..3].eq_ignore_ascii_case(m))
+                .map(|m| m as u64 + 1);
+        } else if year.is_none() && is_number(token, 2..=4) {
+            year = token.parse::<u64>().ok().map(|y| match y {
+                0..=69 => y + 2000,
+                70..=99 => y + 1900,
+                y => y,
+            });
+        }
+    }
+    let (time, day, month, year) = (time?, day?, month?, year?);
+    if !(1..=31).contains(&day) || year < 1601 || time >= 24 * 3600 {
+        return None;
+    }
+    if year < 1970 {
+        return Some(0);
+    }
+    // days since the epoch of the civil date (Howard Hinnant's algorithm)
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = (y % 400) * 365 + (y % 400) / 4 - (y % 400) / 100 + day_of_year;
+    let days = (y / 400) * 146097 + day_of_era - 719468;
+    Some(days * 86400 + time)
+}
+
+/// whether the domain is a public suffix (a cookie for it would be sent to every site under it):
+/// without the whole public suffix list, top-level domains and the usual second-level
+/// registries of the country code domains (co.uk, com.au...) are recognized
+fn is_public_suffix(domain: &str) -> bool {
+    const REGISTRIES: [&str; 17] = [
+        "ac", "co", "com", "edu", "go", "gob", "gov", "gv", "ltd", "mil", "ne", "net", "nic", "or",
+        "org", "plc", "sch",
+    ];
+    let labels: Vec<&str> = domain.split('.').collect();
+    match labels[..] {
+        [_] => true,
+        [registry, country] => country.len() == 2 && REGISTRIES.contains(&registry),
+        _ => false,
+    }
+}
+
+/// host == domain, or host is a subdomain of domain (RFC 6265 §5.1.3)
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host[..host.len() - domain.len()].ends_with('.')
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+/// the request path is the cookie path, or below it (RFC 6265 §5.1.4)
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+impl StoredCookie {
+    /// parse a Set-Cookie header received from url, None if the cookie must be ignored
+    /// (malformed, or for a domain the host has no authority over)
+    fn parse(header: &str, url: &url::Url, now: u64) -> Option<StoredCookie> {
+        let host = url.host_str()?.to_lowercase();
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        // the directory of the request path
+        let default_path = match url.path().rfind('/') {
+            Some(0) | None => "/",
+            Some(i) => &url.path()[..i],
+        };
+        let mut cookie = StoredCookie {
+            domain: host.clone(),
+            path: default_path.to_string(),
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+            host_only: true,
+            secure: false,
+            expires: None,
+        };
+        if cookie.name.is_empty() {
+            return None;
+        }
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    // a public suffix is only allowed as the host itself, for a host-only cookie
+                    if is_public_suffix(&domain) {
+                        if domain != host {
+                            return None;
+                        }
+                    } else {
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    }
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" if cookie.expires.is_none() => cookie.expires = parse_cookie_date(value),
+                _ => {}
+            }
+        }
+        // Max-Age has precedence over Expires
+        if let Some(age) = max_age {
+            cookie.expires = Some(if age <= 0 { 0 } else { now + age as u64 });
+        }
+        Some(cookie)
+    }
+
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.map_or(false, |expires| expires <= now)
+    }
+
+    fn matches(&self, url: &url::Url, now: u64) -> bool {
+        let host = url.host_str().unwrap_or("").to_lowercase();
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+        domain_ok
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+}
+
+/// quote a string for a POSIX shell
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// split a command line into words, like a POSIX shell (without expansions)
+fn shell_split(line: &str) -> Result<Vec<String>, SniprunError> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(w) = word.take() {
+                    words.push(w);
+                }
+            }
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => w.push(c),
+                        None => {
+                            return Err(SniprunError::CustomError(String::from(
+                                "Unterminated quote in the curl command",
+                            )))
+                        }
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) if "$`\"\\\n".contains(c) => w.push(c),
+                            Some(c) => {
+                                w.push('\\');
+                                w.push(c)
+                            }
+                            None => {}
+                        },
+                        Some(c) => w.push(c),
+                        None => {
+                            return Err(SniprunError::CustomError(String::from(
+                                "Unterminated quote in the curl command",
+                            )))
+                        }
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    word.get_or_insert_with(String::new).push(c);
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// sends the matching cookies of the jar, and stores the ones received
+#[allow(clippy::result_large_err)] // signature required by ureq
+fn cookie_middleware(
+    request: ureq::Request,
+    next: ureq::MiddlewareNext,
+) -> Result<ureq::Response, ureq::Error> {
+    let now = unix_now();
+    let request_url = url::Url::parse(request.url()).ok();
+    let cookies: Vec<String> = match (&request_url, COOKIE_JAR.lock().unwrap().as_ref()) {
+        (Some(url), Some(jar)) => jar
+            .iter()
+            .filter(|c| c.matches(url, now))
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect(),
+        _ => vec![],
+    };
+    let request = if cookies.is_empty() {
+        request
+    } else {
+        let header = request
+            .header("Cookie")
+            .map(|h| format!("{}; {}", h, cookies.join("; ")))
+            .unwrap_or_else(|| cookies.join("; "));
+        request.set("Cookie", &header)
+    };
+
+    let result = next.handle(request);
+    let response = match &result {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(_) => return result,
+    };
+    if let (Ok(url), Some(jar)) = (
+        url::Url::parse(response.get_url()),
+        COOKIE_JAR.lock().unwrap().as_mut(),
+    ) {
+        for header in response.all("set-cookie") {
+            if let Some(cookie) = StoredCookie::parse(header, &url, now) {
+                jar.retain(|c| {
+                    (&c.domain, &c.path, &c.name) != (&cookie.domain, &cookie.path, &cookie.name)
+                });
+                if !cookie.is_expired(now) {
+                    jar.push(cookie);
+                }
+            }
+        }
+        jar.retain(|c| !c.is_expired(now));
+    }
+    result
+}
 
 #[derive(Clone)]
 #[allow(non_camel_case_types)]
@@ -13,6 +335,791 @@ pub struct Http_original {
 
 impl ReplLikeInterpreter for Http_original {}
 
+impl Http_original {
+    fn get_agent() -> ureq::Agent {
+        AGENT
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| {
+                ureq::AgentBuilder::new()
+                    .middleware(cookie_middleware)
+                    // runs can be minutes apart, pooled connections would mostly be stale
+                    .max_idle_connections(0)
+                    .build()
+            })
+            .clone()
+    }
+
+    fn cookies_file(data: &DataHolder) -> Option<String> {
+        let save_cookies = Http_original::get_interpreter_option(data, "save_cookies")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if save_cookies {
+            Some(data.work_dir.clone() + "/http_original/cookies.json")
+        } else {
+            None
+        }
+    }
+
+    /// initialize the cookie jar, from the saved cookies if the 'save_cookies' option is set
+    fn load_cookies(data: &DataHolder) {
+        let mut jar = COOKIE_JAR.lock().unwrap();
+        if jar.is_some() {
+            return;
+        }
+        let saved: Vec<JsonValue> = Http_original::cookies_file(data)
+            .and_then(|file| std::fs::read_to_string(file).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let field = |c: &JsonValue, key: &str| c.get(key)?.as_str().map(|s| s.to_string());
+        let flag = |c: &JsonValue, key: &str| c.get(key).and_then(|v| v.as_bool());
+        let now = unix_now();
+        *jar = Some(
+            saved
+                .iter()
+                .filter_map(|c| {
+                    Some(StoredCookie {
+                        domain: field(c, "domain")?,
+                        path: field(c, "path")?,
+                        name: field(c, "name")?,
+                        value: field(c, "value")?,
+                        host_only: flag(c, "host_only").unwrap_or(true),
+                        secure: flag(c, "secure").unwrap_or(false),
+                        expires: c.get("expires").and_then(|v| v.as_u64()),
+                    })
+                })
+                .filter(|c| !c.is_expired(now))
+                .collect(),
+        );
+    }
+
+    fn save_cookies(data: &DataHolder) {
+        let file = match Http_original::cookies_file(data) {
+            Some(file) => file,
+            None => return,
+        };
+        let cookies: Vec<JsonValue> = COOKIE_JAR
+            .lock()
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(|c| {
+                ureq::json!({
+                    "domain": c.domain,
+                    "path": c.path,
+                    "name": c.name,
+                    "value": c.value,
+                    "host_only": c.host_only,
+                    "secure": c.secure,
+                    "expires": c.expires,
+                })
+            })
+            .collect();
+        let _ = std::fs::create_dir_all(data.work_dir.clone() + "/http_original");
+        if let Err(e) = write(&file, JsonValue::from(cookies).to_string()) {
+            info!("could not save the http cookies: {}", e);
+        }
+    }
+
+    /// list the cookies of the jar, or clear it (action 'clear'), for :SnipHttpCookies
+    pub fn cookies(data: &DataHolder, action: &str) -> String {
+        Http_original::load_cookies(data);
+        if action.trim() == "clear" {
+            if let Some(jar) = COOKIE_JAR.lock().unwrap().as_mut() {
+                jar.clear();
+            }
+            Http_original::save_cookies(data);
+            return String::from("Http cookie jar cleared");
+        }
+        let now = unix_now();
+        let jar = COOKIE_JAR.lock().unwrap();
+        let cookies: Vec<String> = jar
+            .iter()
+            .flatten()
+            .filter(|c| !c.is_expired(now))
+            .map(|c| {
+                // subdomains included, as in the Netscape cookies files
+                let dot = if c.host_only { "" } else { "." };
+                format!("{}{}{}  {}={}", dot, c.domain, c.path, c.name, c.value)
+            })
+            .collect();
+        if cookies.is_empty() {
+            String::from("Http cookie jar is empty")
+        } else {
+            cookies.join("\n")
+        }
+    }
+
+    /// the Authorization header from the 'auth' option: a token from the environment
+    /// variable 'bearer_env', or the 'basic_user_env' and 'basic_password_env' credentials
+    fn default_authorization(&self) -> Option<String> {
+        let auth = Http_original::get_interpreter_option(&self.data, "auth")?;
+        let auth = auth.as_map()?;
+        let env_var = |key: &str| {
+            auth.iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .and_then(|(_, v)| v.as_str())
+                .and_then(|var| std::env::var(var).ok())
+        };
+        if let Some(token) = env_var("bearer_env") {
+            return Some(format!("Bearer {}", token));
+        }
+        let user = env_var("basic_user_env")?;
+        let password = env_var("basic_password_env").unwrap_or_default();
+        Some(Http_original::basic_authorization(&user, &password))
+    }
+
+    fn basic_authorization(user: &str, password: &str) -> String {
+        format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password))
+        )
+    }
+
+    /// 'Basic user password' and 'Basic user:password' headers are encoded to base64
+    fn encode_authorization(value: &str) -> String {
+        match value.trim().strip_prefix("Basic ") {
+            Some(credentials) if credentials.contains([' ', ':']) => {
+                let credentials = credentials.trim();
+                let (user, password) = credentials
+                    .split_once(':')
+                    .or_else(|| credentials.split_once(' '))
+                    .unwrap_or((credentials, ""));
+                Http_original::basic_authorization(user.trim(), password.trim())
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// the variables available to the requests, by increasing precedence: the .env file,
+    /// the active environment (see the 'environment' option), and the @var definitions
+    /// of the buffer and of the snippet
+    fn get_variables(&self) -> HashMap<String, String> {
+        let mut variables = HashMap::new();
+        let root = std::path::Path::new(&self.data.projectroot);
+
+        if let Ok(content) = std::fs::read_to_string(root.join(".env")) {
+            variables.extend(Http_original::parse_dotenv(&content));
+        }
+
+        let environment = Http_original::get_interpreter_option(&self.data, "environment")
+            .and_then(|v| v.as_str().map(|s| s.to_string()));
+        for env_file in [ENV_FILE, PRIVATE_ENV_FILE] {
+            if let Ok(content) = std::fs::read_to_string(root.join(env_file)) {
+                match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(environments) => {
+                        variables.extend(Http_original::env_variables(&environments, SHARED_ENV));
+                        if let Some(env) = &environment {
+                            variables.extend(Http_original::env_variables(&environments, env));
+                        }
+                    }
+                    Err(e) => info!("could not parse {}: {}", env_file, e),
+                }
+            }
+        }
+
+        variables.extend(Http_original::parse_definitions(
+            &Http_original::get_buffer_content(&self.data),
+        ));
+        variables.extend(Http_original::parse_definitions(&self.code));
+        variables
+    }
+
+    /// parse the requests of the snippet, once the variables are substituted
+    fn parse_requests(&self) -> Result<Vec<http_rest_file::model::Request>, SniprunError> {
+        let variables = self.get_variables();
+        let code = Http_original::substitute_variables(
+            &Http_original::normalize_names(&Http_original::remove_definitions(&self.code)),
+            &variables,
+        );
+        let FileParseResult { requests, errs } = http_rest_file::Parser::parse(&code, false);
+
+        if !errs.is_empty() {
+            return Err(SniprunError::RuntimeError(format!("{errs:?}")));
+        }
+
+        if requests.is_empty() {
+            return Err(SniprunError::RuntimeError("No requests".to_string()));
+        }
+        Ok(requests)
+    }
+
+    /// resolve the references to previous responses, and add the authorization
+    fn prepare_request(
+        &self,
+        req: http_rest_file::model::Request,
+        named_responses: &serde_json::Map<String, JsonValue>,
+    ) -> Result<PreparedRequest, SniprunError> {
+        // values from the previous responses, '{{login.response.body.$.token}}'
+        let resolve = |text: &str| {
+            Http_original::substitute_with(text, |reference| {
+                Http_original::resolve_reference(named_responses, reference)
+            })
+        };
+        let line = req.request_line;
+
+        let url = match line.target {
+            RequestTarget::Absolute { uri } => resolve(&uri),
+            RequestTarget::RelativeOrigin { uri } => resolve(&uri),
+            // a reference to a previous response is not a valid target until resolved
+            RequestTarget::InvalidTarget(uri) if uri.contains("{{") => resolve(&uri),
+            _ => return Err(SniprunError::RuntimeError("Invalid url".to_string())),
+        };
+
+        let method = match line.method {
+            WithDefault::Some(method) => method,
+            _ => HttpMethod::GET,
+        };
+        let supported = matches!(
+            method,
+            HttpMethod::DELETE
+                | HttpMethod::GET
+                | HttpMethod::PATCH
+                | HttpMethod::POST
+                | HttpMethod::PUT
+                | HttpMethod::HEAD
+                | HttpMethod::OPTIONS
+        );
+        if !supported {
+            return Err(SniprunError::InterpreterLimitationError(
+                "Unsupported method".to_string(),
+            ));
+        }
+
+        let mut headers = vec![];
+        if let Some(authorization) = self.default_authorization() {
+            if !req
+                .headers
+                .iter()
+                .any(|h| h.key.eq_ignore_ascii_case("authorization"))
+            {
+                headers.push((String::from("Authorization"), authorization));
+            }
+        }
+        for header in req.headers.into_iter() {
+            let mut value = resolve(&header.value);
+            if header.key.eq_ignore_ascii_case("authorization") {
+                value = Http_original::encode_authorization(&value);
+            }
+            headers.push((header.key, value));
+        }
+
+        let mut body = resolve(&req.body.to_string());
+        let graphql = headers.iter().any(|(key, value)| {
+            key.eq_ignore_ascii_case("x-request-type") && value.eq_ignore_ascii_case("graphql")
+        }) || body.trim_start().starts_with("```graphql");
+        if graphql {
+            headers.retain(|(key, _)| !key.eq_ignore_ascii_case("x-request-type"));
+            if !headers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            {
+                headers.push((
+                    String::from("Content-Type"),
+                    String::from("application/json"),
+                ));
+            }
+            body = Http_original::graphql_payload(&body)?;
+        }
+
+        Ok(PreparedRequest {
+            name: req.name,
+            method,
+            url,
+            headers,
+            body,
+            no_cookie_jar: req.settings.no_cookie_jar == Some(true),
+            graphql,
+        })
+    }
+
+    /// wrap a GraphQL body (the query, in a ```graphql fence or not, optionally followed
+    /// by the JSON variables after an empty line) into the JSON payload
+    fn graphql_payload(body: &str) -> Result<String, SniprunError> {
+        let body = body.trim();
+        let (query, variables) = match body.strip_prefix("```graphql") {
+            Some(fenced) => match fenced.split_once("```") {
+                Some((query, rest)) => (query.trim(), rest.trim()),
+                None => {
+                    return Err(SniprunError::RuntimeError(String::from(
+                        "Unterminated ```graphql block",
+                    )))
+                }
+            },
+            None => match body.rsplit_once("\n\n") {
+                Some((query, rest)) if rest.trim_start().starts_with('{') => {
+                    (query.trim(), rest.trim())
+                }
+                _ => (body, ""),
+            },
+        };
+
+        let mut payload = serde_json::Map::new();
+        payload.insert(String::from("query"), JsonValue::from(query));
+        if !variables.is_empty() {
+            let variables: JsonValue = serde_json::from_str(variables).map_err(|e| {
+                SniprunError::RuntimeError(format!("Invalid GraphQL variables: {}", e))
+            })?;
+            payload.insert(String::from("variables"), variables);
+        }
+        Ok(JsonValue::Object(payload).to_string())
+    }
+
+    /// the messages of the 'errors' array of a GraphQL response, if any
+    fn graphql_errors(body: &[u8]) -> Vec<String> {
+        let response: JsonValue = match serde_json::from_slice(body) {
+            Ok(response) => response,
+            Err(_) => return vec![],
+        };
+        response
+            .get("errors")
+            .and_then(|errors| errors.as_array())
+            .map(|errors| {
+                errors
+                    .iter()
+                    .map(|e| match e.get("message").and_then(|m| m.as_str()) {
+                        Some(message) => message.to_string(),
+                        None => e.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// the requests of the snippet, as curl commands
+    fn to_curl(&self) -> Result<String, SniprunError> {
+        let named_responses = self.load_responses();
+        let mut commands = vec![];
+        for req in self.parse_requests()? {
+            let req = self.prepare_request(req, &named_responses)?;
+            let mut command = vec![match req.method {
+                HttpMethod::GET => format!("curl {}", shell_quote(&req.url)),
+                HttpMethod::HEAD => format!("curl --head {}", shell_quote(&req.url)),
+                _ => format!(
+                    "curl -X {} {}",
+                    req.method.to_string(),
+                    shell_quote(&req.url)
+                ),
+            }];
+            for (key, value) in req.headers.iter() {
+                command.push(format!(
+                    "-H {}",
+                    shell_quote(&format!("{}: {}", key, value))
+                ));
+            }
+            if !req.body.trim().is_empty() {
+                command.push(format!("--data-raw {}", shell_quote(req.body.trim())));
+            }
+            commands.push(command.join(" \\\n  "));
+        }
+        Ok(commands.join("\n\n"))
+    }
+
+    /// convert a curl command (possibly on several lines) to the .http syntax
+    fn from_curl(curl: &str) -> Result<String, SniprunError> {
+        let words = shell_split(&curl.replace("\\\n", " "))?;
+        let mut words = words.into_iter();
+        if words.next().as_deref() != Some("curl") {
+            return Err(SniprunError::CustomError(String::from(
+                "Not a curl command",
+            )));
+        }
+
+        let mut method = None;
+        let mut url = None;
+        let mut headers = vec![];
+        let mut data: Vec<String> = vec![];
+        let mut get = false;
+        while let Some(word) = words.next() {
+            // --option=value is the same as --option value
+            let (option, inline_value) = match word.split_once('=') {
+                Some((o, v)) if word.starts_with("--") => (o.to_string(), Some(v.to_string())),
+                _ => (word.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| words.next())
+                    .ok_or_else(|| {
+                        SniprunError::CustomError(format!(
+                            "Missing value for curl option {}",
+                            option
+                        ))
+                    })
+            };
+            match option.as_str() {
+                "-X" | "--request" => method = Some(value()?),
+                "-H" | "--header" => headers.push(value()?),
+                "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                    data.push(value()?)
+                }
+                "--json" => {
+                    data.push(value()?);
+                    headers.push(String::from("Content-Type: application/json"));
+                    headers.push(String::from("Accept: application/json"));
+                }
+                "-u" | "--user" => headers.push(format!("Authorization: Basic {}", value()?)),
+                "-A" | "--user-agent" => headers.push(format!("User-Agent: {}", value()?)),
+                "-e" | "--referer" => headers.push(format!("Referer: {}", value()?)),
+                "-b" | "--cookie" => headers.push(format!("Cookie: {}", value()?)),
+                "--url" => url = Some(value()?),
+                "-I" | "--head" => method = Some(String::from("HEAD")),
+                "-G" | "--get" => get = true,
+                // options with a value that don't change the request
+                "-o" | "--output" | "-m" | "--max-time" | "--connect-timeout" | "-w"
+                | "--write-out" | "--retry" | "-x" | "--proxy" => {
+                    value()?;
+                }
+                o if o.starts_with('-') => info!("ignoring curl option {}", o),
+                _ => url = Some(word),
+            }
+        }
+
+        let mut url = url
+            .ok_or_else(|| SniprunError::CustomError(String::from("No url in the curl command")))?;
+        // with -G, the data is sent as query parameters
+        if get && !data.is_empty() {
+            url = format!(
+                "{}{}{}",
+                url,
+                if url.contains('?') { '&' } else { '?' },
+                data.join("&")
+            );
+            data.clear();
+        }
+        let method = method.unwrap_or_else(|| {
+            if data.is_empty() {
+                String::from("GET")
+            } else {
+                String::from("POST")
+            }
+        });
+        let has_content_type = headers
+            .iter()
+            .any(|h| h.to_lowercase().starts_with("content-type:"));
+        if !data.is_empty() && !has_content_type {
+            // curl's default for -d
+            headers.push(String::from(
+                "Content-Type: application/x-www-form-urlencoded",
+            ));
+        }
+
+        let mut request = vec![format!("{} {}", method, url)];
+        request.extend(headers);
+        if !data.is_empty() {
+            request.push(String::new());
+            request.push(data.join("&"));
+        }
+        Ok(request.join("\n"))
+    }
+
+    /// ':SnipHttpCurl' actions on the selected request(s): 'to_curl' (shown, or yanked to
+    /// the register given after the action), or 'from_curl' (replaced in the buffer)
+    pub fn curl_command(data: &DataHolder, action: &str) -> Result<String, SniprunError> {
+        let mut words = action.split_whitespace();
+        match words.next() {
+            Some("from_curl") => {
+                let http = Http_original::from_curl(&data.current_bloc)?;
+                if let Some(nvim_instance) = data.nvim_instance.clone() {
+                    let mut nvim = nvim_instance.lock().unwrap();
+                    let lines = http.lines().map(|l| l.to_string()).collect();
+                    let buffer = nvim.get_current_buf().map_err(|e| {
+                        SniprunError::CustomError(format!("Could not get the buffer: {}", e))
+                    })?;
+                    buffer
+                        .set_lines(&mut nvim, data.range[0] - 1, data.range[1], false, lines)
+                        .map_err(|e| {
+                            SniprunError::CustomError(format!("Could not replace the lines: {}", e))
+                        })?;
+                }
+                Ok(http)
+            }
+            _ => {
+                let mut interpreter = Http_original::new(data.clone());
+                interpreter.fetch_code()?;
+                let curl = interpreter.to_curl()?;
+                if let (Some(register), Some(nvim_instance)) =
+                    (words.next(), data.nvim_instance.clone())
+                {
+                    nvim_instance
+                        .lock()
+                        .unwrap()
+                        .call_function(
+                            "setreg",
+                            vec![
+                                neovim_lib::Value::from(register),
+                                neovim_lib::Value::from(curl.as_str()),
+                            ],
+                        )
+                        .map_err(|e| {
+                            SniprunError::CustomError(format!("Could not set register: {}", e))
+                        })?;
+                    return Ok(format!("curl command copied to register {}", register));
+                }
+                Ok(curl)
+            }
+        }
+    }
+
+    /// responses of the named requests, as a map name -> {status, headers, body},
+    /// kept in sniprun's memory (emptied by :SnipReplMemoryClean and :SnipReset)
+    fn load_responses(&self) -> serde_json::Map<String, JsonValue> {
+        serde_json::from_str(self.read_previous_code().trim()).unwrap_or_default()
+    }
+
+    fn save_responses(&self, responses: &serde_json::Map<String, JsonValue>) {
+        // the memory holds the whole map, replaced at each save
+        self.clear();
+        self.save_code(JsonValue::Object(responses.clone()).to_string());
+    }
+
+    /// the headers of a named response, for the {{name.response.headers.Header-Name}}
+    /// references: repeated headers are joined with ", ", except Set-Cookie whose cookies
+    /// (without their attributes) are joined with "; ", ready for a Cookie header
+    fn headers_map(headers: &[(String, String)]) -> serde_json::Map<String, JsonValue> {
+        let mut map: serde_json::Map<String, JsonValue> = serde_json::Map::new();
+        for (name, value) in headers {
+            let (value, separator) = if name.eq_ignore_ascii_case("set-cookie") {
+                (value.split(';').next().unwrap_or("").trim(), "; ")
+            } else {
+                (value.as_str(), ", ")
+            };
+            let joined = match map.get(name).and_then(|v| v.as_str()) {
+                Some(previous) => format!("{}{}{}", previous, separator, value),
+                None => value.to_string(),
+            };
+            map.insert(name.clone(), JsonValue::from(joined));
+        }
+        map
+    }
+
+    /// status line, headers (see the 'headers' option), timing and size, then the body:
+    /// pretty-printed if it's JSON, raw if it's text, or saved to a file if binary
+    fn format_response(
+        &self,
+        status_line: &str,
+        headers: &[(String, String)],
+        content_type: &str,
+        body: &[u8],
+        elapsed: std::time::Duration,
+    ) -> String {
+        let mut view = vec![status_line.to_string()];
+
+        // 'all' (default), 'none', or a list of header names
+        let shown_headers = Http_original::get_interpreter_option(&self.data, "headers");
+        let is_shown = |name: &str| match &shown_headers {
+            None => true,
+            Some(v) if v.as_str() == Some("all") => true,
+            Some(v) => v
+                .as_array()
+                .map(|names| {
+                    names
+                        .iter()
+                        .any(|n| n.as_str().map_or(false, |n| n.eq_ignore_ascii_case(name)))
+                })
+                .unwrap_or(false),
+        };
+        for (name, value) in headers.iter().filter(|(name, _)| is_shown(name)) {
+            view.push(format!("{}: {}", name, value));
+        }
+        view.push(format!(
+            "--- {} ms, {} ---",
+            elapsed.as_millis(),
+            Http_original::format_size(body.len())
+        ));
+
+        let body_view = match std::str::from_utf8(body) {
+            Ok(text)
+                if content_type.contains("json")
+                    || text.trim_start().starts_with('{')
+                    || text.trim_start().starts_with('[') =>
+            {
+                serde_json::from_str::<JsonValue>(text)
+                    .and_then(|json| serde_json::to_string_pretty(&json))
+                    .unwrap_or_else(|_| text.to_string())
+            }
+            Ok(text) => text.to_string(),
+            Err(_) => self.save_binary_body(content_type, body),
+        };
+        if !body_view.is_empty() {
+            view.push(body_view);
+        }
+        view.join("\n")
+    }
+
+    fn format_size(size: usize) -> String {
+        match size {
+            s if s < 1024 => format!("{} B", s),
+            s if s < 1024 * 1024 => format!("{:.1} KB", s as f64 / 1024.0),
+            s => format!("{:.1} MB", s as f64 / (1024.0 * 1024.0)),
+        }
+    }
+
+    /// save a binary body to the directory given by the 'save_binary_body' option
+    /// (or to sniprun's cache if it's `true`), returning a note for the output
+    fn save_binary_body(&self, content_type: &str, body: &[u8]) -> String {
+        let dir = match Http_original::get_interpreter_option(&self.data, "save_binary_body") {
+            Some(v) if v.as_bool() == Some(true) => format!("{}/http_original", self.data.work_dir),
+            Some(v) if v.as_str().is_some() => v.as_str().unwrap().to_string(),
+            _ => return format!("[binary body: {}]", Http_original::format_size(body.len())),
+        };
+        let extension = content_type
+            .rsplit('/')
+            .next()
+            .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("bin");
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let path = format!("{}/response_{}.{}", dir, millis, extension);
+        match std::fs::create_dir_all(&dir).and_then(|_| write(&path, body)) {
+            Ok(()) => format!("[binary body saved to {}]", path),
+            Err(e) => format!("[binary body: could not save it to {}: {}]", path, e),
+        }
+    }
+
+    /// the name given with '# @name login', normalized as '# @name=login' for the parser
+    fn normalize_names(code: &str) -> String {
+        let name = Regex::new(r"(?m)^\s*(#|//)\s*@name\s+([\w-]+)\s*$").unwrap();
+        name.replace_all(code, "# @name=$2").to_string()
+    }
+
+    /// the value of a 'login.response.body.$.token', 'login.response.headers.Set-Cookie'
+    /// or 'login.response.status' reference
+    fn resolve_reference(
+        responses: &serde_json::Map<String, JsonValue>,
+        reference: &str,
+    ) -> Option<String> {
+        let (name, path) = reference.split_once(".response.")?;
+        let response = responses.get(name)?;
+        if path == "status" {
+            return response.get("status").map(|s| s.to_string());
+        }
+        if let Some(header) = path.strip_prefix("headers.") {
+            return response
+                .get("headers")?
+                .as_object()?
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(header))
+                .and_then(|(_, value)| value.as_str().map(|s| s.to_string()));
+        }
+        let body = response.get("body")?.as_str()?;
+        match path {
+            "body" | "body.*" => Some(body.to_string()),
+            _ => {
+                let json_path = path.strip_prefix("body.$")?;
+                let body_json: JsonValue = serde_json::from_str(body).ok()?;
+                match Http_original::json_path(&body_json, json_path)? {
+                    JsonValue::String(s) => Some(s.clone()),
+                    other => Some(other.to_string()),
+                }
+            }
+        }
+    }
+
+    /// follow a JSONPath-like path ('.key', '[index]' or "['key']" steps) from the root
+    fn json_path<'a>(root: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+        let step = Regex::new(r"^(?:\.([^.\[\]]+)|\[(\d+)\]|\['([^']+)'\])").unwrap();
+        let mut value = root;
+        let mut rest = path;
+        while !rest.is_empty() {
+            let captures = step.captures(rest)?;
+            value = if let Some(index) = captures.get(2) {
+                value.get(index.as_str().parse::<usize>().ok()?)?
+            } else {
+                let key = captures.get(1).or_else(|| captures.get(3))?;
+                value.get(key.as_str())?
+            };
+            rest = &rest[captures[0].len()..];
+        }
+        Some(value)
+    }
+
+    /// 'KEY=value' lines of a .env file
+    fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+        content
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|l| l.trim_start_matches("export ").split_once('='))
+            .map(|(key, value)| {
+                let value = value.trim();
+                let unquoted = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                    .unwrap_or(value);
+                (key.trim().to_string(), unquoted.to_string())
+            })
+            .collect()
+    }
+
+    /// the variables of one environment of a http-client.env.json file
+    fn env_variables(environments: &serde_json::Value, name: &str) -> Vec<(String, String)> {
+        match environments.get(name).and_then(|env| env.as_object()) {
+            Some(env) => env
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// file-level '@var = value' definitions
+    fn parse_definitions(code: &str) -> Vec<(String, String)> {
+        let definition = Regex::new(r"^@([A-Za-z_][\w.-]*)\s*=\s*(.*)$").unwrap();
+        code.lines()
+            .filter_map(|l| definition.captures(l.trim()))
+            .map(|c| (c[1].to_string(), c[2].trim().to_string()))
+            .collect()
+    }
+
+    /// remove the '@var = value' lines, which are not part of any request
+    fn remove_definitions(code: &str) -> String {
+        let definition = Regex::new(r"^\s*@[A-Za-z_][\w.-]*\s*=").unwrap();
+        code.lines()
+            .filter(|l| !definition.is_match(l))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// replace the {{variable}}s, leaving the unknown ones as-is
+    fn substitute_variables(code: &str, variables: &HashMap<String, String>) -> String {
+        Http_original::substitute_with(code, |name| {
+            variables.get(name).cloned().or_else(|| {
+                name.strip_prefix("$env.")
+                    .and_then(|var| std::env::var(var).ok())
+            })
+        })
+    }
+
+    /// replace the {{placeholder}}s whose value is found by the lookup
+    fn substitute_with(code: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+        let placeholder = Regex::new(r"\{\{\s*([^{}\s]+)\s*\}\}").unwrap();
+        let mut result = code.to_string();
+        // variables can be defined with other variables
+        for _ in 0..5 {
+            let substituted = placeholder
+                .replace_all(&result, |c: &regex::Captures| {
+                    lookup(&c[1]).unwrap_or_else(|| c[0].to_string())
+                })
+                .to_string();
+            if substituted == result {
+                break;
+            }
+            result = substituted;
+        }
+        result
+    }
+}
+
 impl Interpreter for Http_original {
     fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Http_original> {
         Box::new(Http_original {
@@ -97,60 +1204,106 @@ impl Interpreter for Http_original {
     }
 
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let FileParseResult { requests, errs } = http_rest_file::Parser::parse(&self.code, false);
-
-        if !errs.is_empty() {
-            return Err(SniprunError::RuntimeError(format!("{errs:?}")));
-        }
-
-        if requests.is_empty() {
-            return Err(SniprunError::RuntimeError("No requests".to_string()));
-        }
+        let requests = self.parse_requests()?;
 
         let mut responses = Vec::new();
+        let mut named_responses = self.load_responses();
+        Http_original::load_cookies(&self.data);
+        let agent = Http_original::get_agent();
 
         for req in requests.into_iter() {
-            let line = req.request_line;
-
-            let url = match line.target {
-                RequestTarget::Absolute { uri } => uri,
-                RequestTarget::RelativeOrigin { uri } => uri,
-                _ => return Err(SniprunError::RuntimeError("Invalid url".to_string())),
-            };
-
-            let mut r = match line.method {
-                WithDefault::Some(HttpMethod::DELETE) => ureq::delete(&url),
-                WithDefault::Some(HttpMethod::GET) => ureq::get(&url),
-                WithDefault::Some(HttpMethod::PATCH) => ureq::patch(&url),
-                WithDefault::Some(HttpMethod::POST) => ureq::post(&url),
-                WithDefault::Some(HttpMethod::PUT) => ureq::put(&url),
-                _ => {
-                    return Err(SniprunError::InterpreterLimitationError(
-                        "Unsupported method".to_string(),
-                    ))
-                }
+            let req = self.prepare_request(req, &named_responses)?;
+            // '# @no-cookie-jar' requests don't use (nor fill) the cookie jar
+            let mut r = if req.no_cookie_jar {
+                ureq::request(&req.method.to_string(), &req.url)
+            } else {
+                agent.request(&req.method.to_string(), &req.url)
             };
-
-            for header in req.headers.into_iter() {
-                r = r.set(&header.key, &header.value);
+            for (key, value) in req.headers.iter() {
+                r = r.set(key, value);
             }
 
-            match r.send(Cursor::new(req.body.to_string())) {
-                Ok(resp) => {
-                    let status = resp.status();
-                    responses.push(
-                        resp.into_string().unwrap_or("".to_string())
-                            + "--- status : "
-                            + &status.to_string()
-                            + " ---",
-                    );
-                }
+            let start = std::time::Instant::now();
+            let (resp, failed) = match r.send(Cursor::new(req.body)) {
+                Ok(resp) => (resp, false),
+                // 4xx and 5xx responses are still shown, as errors
+                Err(ureq::Error::Status(_, resp)) => (resp, true),
                 Err(why) => {
                     return Err(SniprunError::CustomError(format!(
                         "Error sending request: {why}"
                     )));
                 }
+            };
+
+            let status = resp.status();
+            let status_line = format!("{} {} {}", resp.http_version(), status, resp.status_text());
+            // one (name, value) per header line: ureq lists a name once per value
+            let mut names: Vec<String> = vec![];
+            for name in resp.headers_names() {
+                if !names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+                    names.push(name);
+                }
             }
+            let headers: Vec<(String, String)> = names
+                .iter()
+                .flat_map(|h| {
+                    resp.all(h)
+                        .into_iter()
+                        .map(move |v| (h.clone(), v.to_string()))
+                })
+                .collect();
+            let content_type = resp.content_type().to_string();
+            let mut body = Vec::new();
+            if let Err(e) = resp.into_reader().read_to_end(&mut body) {
+                return Err(SniprunError::CustomError(format!(
+                    "Error reading response: {e}"
+                )));
+            }
+            let elapsed = start.elapsed();
+            Http_original::save_cookies(&self.data);
+
+            // comments may also be used as names by the parser, keep only identifiers
+            if let Some(name) = req.name.filter(|n| {
+                n.chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            }) {
+                let headers_map = Http_original::headers_map(&headers);
+                named_responses.insert(
+                    name,
+                    ureq::json!({
+                        "status": status,
+                        "headers": headers_map,
+                        "body": String::from_utf8_lossy(&body),
+                    }),
+                );
+                self.save_responses(&named_responses);
+            }
+
+            let mut view =
+                self.format_response(&status_line, &headers, &content_type, &body, elapsed);
+            // GraphQL servers report errors with a 200 status
+            let graphql_errors = if req.graphql {
+                Http_original::graphql_errors(&body)
+            } else {
+                vec![]
+            };
+            if !graphql_errors.is_empty() {
+                view = format!(
+                    "GraphQL errors:\n- {}\n\n{}",
+                    graphql_errors.join("\n- "),
+                    view
+                );
+            }
+            if failed || !graphql_errors.is_empty() {
+                return Err(SniprunError::RuntimeError(
+                    responses
+                        .into_iter()
+                        .chain(std::iter::once(view))
+                        .collect::<Vec<_>>()
+                        .join("\n---\n\n"),
+                ));
+            }
+            responses.push(view);
         }
 
         Ok(responses.join("\n---\n\n"))
@@ -162,6 +1315,242 @@ mod test_http_original {
     use super::*;
     use serial_test::serial;
 
+    #[test]
+    fn variables_substitution() {
+        let code = "@host = localhost:{{port}}\n@port = 8079\nGET http://{{host}}/{{ missing }}\nAuthorization: {{token}}";
+        let environments: serde_json::Value =
+            serde_json::from_str(r#"{"$shared": {"token": "abc"}, "staging": {"token": "xyz"}}"#)
+                .unwrap();
+        let mut variables: HashMap<String, String> = HashMap::new();
+        variables.extend(Http_original::env_variables(&environments, SHARED_ENV));
+        variables.extend(Http_original::env_variables(&environments, "staging"));
+        variables.extend(Http_original::parse_definitions(code));
+        let request = Http_original::substitute_variables(
+            &Http_original::remove_definitions(code),
+            &variables,
+        );
+        assert_eq!(
+            request,
+            "GET http://localhost:8079/{{ missing }}\nAuthorization: xyz"
+        );
+        assert_eq!(
+            Http_original::parse_dotenv("# comment\nexport API_KEY=\"k\"\nUSER = me\n"),
+            vec![
+                (String::from("API_KEY"), String::from("k")),
+                (String::from("USER"), String::from("me"))
+            ]
+        );
+    }
+
+    #[test]
+    fn response_view() {
+        let interpreter = Http_original::new(DataHolder::new());
+        let headers = vec![(
+            String::from("content-type"),
+            String::from("application/json"),
+        )];
+        let view = interpreter.format_response(
+            "HTTP/1.1 200 OK",
+            &headers,
+            "application/json",
+            br#"{"a":1}"#,
+            std::time::Duration::from_millis(12),
+        );
+        assert_eq!(
+            view,
+            "HTTP/1.1 200 OK\ncontent-type: application/json\n--- 12 ms, 7 B ---\n{\n  \"a\": 1\n}"
+        );
+        let binary = interpreter.format_response(
+            "HTTP/1.1 200 OK",
+            &[],
+            "image/png",
+            &[0x89, 0x50, 0xff, 0xfe],
+            std::time::Duration::from_millis(3),
+        );
+        assert!(binary.ends_with("[binary body: 4 B]"));
+    }
+
+    #[test]
+    fn repeated_headers() {
+        let headers: Vec<(String, String)> = [
+            ("set-cookie", "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT"),
+            ("set-cookie", "b=2; Path=/"),
+            ("vary", "Accept"),
+            ("vary", "Origin"),
+        ]
+        .iter()
+        .map(|(h, v)| (h.to_string(), v.to_string()))
+        .collect();
+        let map = Http_original::headers_map(&headers);
+        assert_eq!(map["set-cookie"], "a=1; b=2");
+        assert_eq!(map["vary"], "Accept, Origin");
+    }
+
+    #[test]
+    fn responses_in_memory() {
+        use crate::InterpreterData;
+        use std::sync::Arc;
+        let memory = Arc::new(Mutex::new(InterpreterData {
+            owner: String::new(),
+            content: String::new(),
+            pid: None,
+        }));
+        let mut data = DataHolder::new();
+        data.interpreter_data = Some(memory.clone());
+        let interpreter = Http_original::new(data);
+        let mut responses = serde_json::Map::new();
+        for name in ["login", "items"] {
+            responses.insert(name.to_string(), ureq::json!({"status": 200}));
+            interpreter.save_responses(&responses);
+        }
+        assert_eq!(interpreter.load_responses(), responses);
+
+        // :SnipReplMemoryClean
+        memory.lock().unwrap().content.clear();
+        assert!(interpreter.load_responses().is_empty());
+    }
+
+    #[test]
+    fn cookies_and_auth() {
+        let url = url::Url::parse("http://api.example.com/v1/users").unwrap();
+        let at = |u: &str| url::Url::parse(u).unwrap();
+        let cookie =
+            StoredCookie::parse("sid=42; Path=/v1; Domain=.example.com; HttpOnly", &url, 0)
+                .unwrap();
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert!(cookie.matches(&url, 0));
+        assert!(cookie.matches(&at("http://example.com/v1"), 0));
+        assert!(!cookie.matches(&at("http://example.org/v1"), 0));
+        assert!(!cookie.matches(&at("http://notexample.com/v1"), 0));
+        // path segments
+        assert!(cookie.matches(&at("http://example.com/v1/"), 0));
+        assert!(!cookie.matches(&at("http://example.com/v10"), 0));
+
+        // host-only by default, with the directory of the request as path
+        let cookie = StoredCookie::parse("sid=1", &url, 0).unwrap();
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/v1");
+        assert!(!cookie.matches(&at("http://sub.api.example.com/v1"), 0));
+
+        // domains the host has no authority over
+        assert_eq!(
+            StoredCookie::parse("a=1; Domain=example.org", &url, 0),
+            None
+        );
+        assert_eq!(StoredCookie::parse("a=1; Domain=com", &url, 0), None);
+        let uk = at("https://shop.example.co.uk/");
+        assert_eq!(StoredCookie::parse("a=1; Domain=co.uk", &uk, 0), None);
+        assert!(StoredCookie::parse("a=1; Domain=example.co.uk", &uk, 0).is_some());
+
+        let secure = StoredCookie::parse("a=1; Secure", &uk, 0).unwrap();
+        assert!(secure.matches(&uk, 0));
+        assert!(!secure.matches(&at("http://shop.example.co.uk/"), 0));
+
+        // expiration
+        assert!(StoredCookie::parse("sid=; Max-Age=0", &url, 100)
+            .unwrap()
+            .is_expired(100));
+        let cookie = StoredCookie::parse("a=1; Max-Age=60", &url, 100).unwrap();
+        assert!(cookie.matches(&url, 159) && !cookie.matches(&url, 160));
+        let cookie =
+            StoredCookie::parse("a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", &url, 0).unwrap();
+        assert_eq!(cookie.expires, Some(1445412480));
+        assert!(cookie.is_expired(unix_now()));
+        assert_eq!(parse_cookie_date("Thu, 01-Jan-70 00:00:01 GMT"), Some(1));
+        assert_eq!(parse_cookie_date("not a date"), None);
+
+        assert_eq!(
+            Http_original::encode_authorization("Basic user pass"),
+            "Basic dXNlcjpwYXNz"
+        );
+        assert_eq!(
+            Http_original::encode_authorization("Basic dXNlcjpwYXNz"),
+            "Basic dXNlcjpwYXNz"
+        );
+    }
+
+    #[test]
+    fn curl_conversions() {
+        let http = Http_original::from_curl(
+            "curl -s 'https://api.example.com/items?page=1' \\\n  -H 'Accept: application/json' \\\n  -u me:secret --data-raw '{\"name\": \"it'\\''s\"}'",
+        )
+        .unwrap();
+        assert_eq!(
+            http,
+            "POST https://api.example.com/items?page=1\nAccept: application/json\nAuthorization: Basic me:secret\nContent-Type: application/x-www-form-urlencoded\n\n{\"name\": \"it's\"}"
+        );
+
+        let mut data = DataHolder::new();
+        data.current_bloc = String::from(
+            "POST https://api.example.com/items\nContent-Type: application/json\n\n{\"a\": \"b\"}",
+        );
+        let mut interpreter = Http_original::new(data);
+        interpreter.fetch_code().unwrap();
+        let curl = interpreter.to_curl().unwrap();
+        assert_eq!(
+            curl,
+            "curl -X POST 'https://api.example.com/items' \\\n  -H 'Content-Type: application/json' \\\n  --data-raw '{\"a\": \"b\"}'"
+        );
+        assert_eq!(
+            shell_split(&curl.replace("\\\n", " ")).unwrap()[7],
+            "{\"a\": \"b\"}"
+        );
+    }
+
+    #[test]
+    fn graphql() {
+        let payload = Http_original::graphql_payload(
+            "```graphql\nquery User($id: ID!) { user(id: $id) { name } }\n```\n{\"id\": 1}",
+        )
+        .unwrap();
+        assert_eq!(
+            payload,
+            r#"{"query":"query User($id: ID!) { user(id: $id) { name } }","variables":{"id":1}}"#
+        );
+        assert_eq!(
+            Http_original::graphql_payload("{ users { name } }").unwrap(),
+            r#"{"query":"{ users { name } }"}"#
+        );
+        assert_eq!(
+            Http_original::graphql_errors(br#"{"data": null, "errors": [{"message": "no user"}]}"#),
+            vec![String::from("no user")]
+        );
+        assert!(Http_original::graphql_errors(br#"{"data": {"a": 1}}"#).is_empty());
+    }
+
+    #[test]
+    fn response_references() {
+        let mut responses = serde_json::Map::new();
+        responses.insert(
+            String::from("login"),
+            ureq::json!({
+                "status": 200,
+                "headers": {"set-cookie": "session=1"},
+                "body": r#"{"token": "abc", "items": [{"id": 7}]}"#
+            }),
+        );
+        let resolve = |reference: &str| Http_original::resolve_reference(&responses, reference);
+        assert_eq!(
+            resolve("login.response.body.$.token"),
+            Some(String::from("abc"))
+        );
+        assert_eq!(
+            resolve("login.response.body.$.items[0].id"),
+            Some(String::from("7"))
+        );
+        assert_eq!(
+            resolve("login.response.headers.Set-Cookie"),
+            Some(String::from("session=1"))
+        );
+        assert_eq!(resolve("login.response.status"), Some(String::from("200")));
+        assert_eq!(resolve("other.response.status"), None);
+        assert_eq!(
+            Http_original::normalize_names("# @name login\nGET http://localhost"),
+            "# @name=login\nGET http://localhost"
+        );
+    }
+
     #[test]
     #[serial]
     fn simple_http_get() {
@@ -174,7 +1563,7 @@ mod test_http_original {
 
         assert!(res.is_ok(), "Could not run http interpreter");
         let data = res.ok().unwrap();
-        let (_body, status) = data.split_once("---").unwrap();
+        let (status, _body) = data.split_once("---").unwrap();
 
         assert!(status.contains("200"));
     }
@@ -195,7 +1584,7 @@ mod test_http_original {
 
         assert!(res.is_ok(), "Could not run http interpreter");
         let data = res.ok().unwrap();
-        let (_body, status) = data.split_once("---").unwrap();
+        let (status, _body) = data.split_once("---").unwrap();
 
         assert!(status.contains("200"));
     }
@@ -221,7 +1610,7 @@ GET http://localhost:8079
 
         let data = res.ok().unwrap();
 
-        let (_body, status) = data.split_once("---").unwrap();
+        let (status, _body) = data.split_once("---").unwrap();
 
         assert!(status.contains("200"));
     }
@@ -245,7 +1634,7 @@ POST http://localhost:8079
 
         assert!(res.is_ok(), "Could not run http interpreter");
         let data = res.ok().unwrap();
-        let (_body, status) = data.split_once("---").unwrap();
+        let (status, _body) = data.split_once("---").unwrap();
 
         assert!(status.contains("200"));
     }
@@ -42,7 +42,7 @@ impl JS_TS_bun {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     // info!("out {}", out_contents);
                     let relevant_content: String = strip_ansi_escapes::strip_str(&out_contents)
@@ -201,34 +201,33 @@ impl Interpreter for JS_TS_bun {
             }
         }
         let interpreter = JS_TS_bun::get_interpreter_or(&self.data, "bun");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg("run")
-            .arg("--silent")
-            .args(bun_opts.split_whitespace())
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = JS_TS_bun::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg("run")
+                .arg("--silent")
+                .args(bun_opts.split_whitespace())
+                .arg(&self.main_file_path),
+            Some("interpreter"),
+        )?;
 
         if output.status.success() {
             //return stdout
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else {
             // return stderr
             if JS_TS_bun::error_truncate(&self.get_data()) == ErrTruncate::Short {
                 Err(SniprunError::RuntimeError(
-                    String::from_utf8(output.stderr.clone())
-                        .unwrap()
+                    decode_output(&output.stderr)
                         .lines()
                         .filter(|l| l.contains("Error:"))
                         .next_back()
-                        .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                        .unwrap_or(&decode_output(&output.stderr))
                         .to_string(),
                 ))
             } else {
-                Err(SniprunError::RuntimeError(
-                    String::from_utf8(output.stderr).unwrap(),
-                ))
+                Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
             }
         }
     }
@@ -265,6 +264,7 @@ impl ReplLikeInterpreter for JS_TS_bun {
                 init_repl_cmd, &self.cache_dir
             );
 
+            JS_TS_bun::check_executable(&self.data, "bun", None)?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
@@ -48,7 +48,7 @@ impl JS_TS_deno {
             if let Ok(mut file) = std::fs::File::open(&err_path) {
                 info!("errfile exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 if res.is_ok() {
                     // info!("errfile could be read : {:?}", err_contents);
                     if err_contents.contains(&end_mark) {
@@ -77,7 +77,7 @@ impl JS_TS_deno {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     // info!("out {}", out_contents);
                     let relevant_content: String = out_contents
@@ -217,35 +217,34 @@ impl Interpreter for JS_TS_deno {
     fn execute(&mut self) -> Result<String, SniprunError> {
         //run the binary and get the std output (or stderr)
         let interpreter = JS_TS_deno::get_interpreter_or(&self.data, "deno");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg("run")
-            .arg("-A")
-            .arg("--unstable")
-            .arg(&self.main_file_path)
-            .env("NO_COLOR", "1")
-            .output()
-            .expect("Unable to start process");
+        let output = JS_TS_deno::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg("run")
+                .arg("-A")
+                .arg("--unstable")
+                .arg(&self.main_file_path)
+                .env("NO_COLOR", "1"),
+            Some("interpreter"),
+        )?;
 
         if output.status.success() {
             //return stdout
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else {
             // return stderr
             if JS_TS_deno::error_truncate(&self.get_data()) == ErrTruncate::Short {
                 Err(SniprunError::RuntimeError(
-                    String::from_utf8(output.stderr.clone())
-                        .unwrap()
+                    decode_output(&output.stderr)
                         .lines()
                         .filter(|l| l.contains("Error:"))
                         .next_back()
-                        .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                        .unwrap_or(&decode_output(&output.stderr))
                         .to_string(),
                 ))
             } else {
-                Err(SniprunError::RuntimeError(
-                    String::from_utf8(output.stderr).unwrap(),
-                ))
+                Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
             }
         }
     }
@@ -282,6 +281,7 @@ impl ReplLikeInterpreter for JS_TS_deno {
                 init_repl_cmd, &self.cache_dir
             );
 
+            JS_TS_deno::check_executable(&self.data, "deno", None)?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
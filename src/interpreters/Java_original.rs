@@ -104,13 +104,15 @@ impl Interpreter for Java_original {
 
         let compiler = Java_original::get_compiler_or(&self.data, "javac");
         //compile it (to the bin_path that arleady points to the rigth path)
-        let output = Command::new(compiler.split_whitespace().next().unwrap())
-            .args(compiler.split_whitespace().skip(1))
-            .arg("-d")
-            .arg(&self.java_work_dir)
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Java_original::run_command(
+            &self.data,
+            Command::new(compiler.split_whitespace().next().unwrap())
+                .args(compiler.split_whitespace().skip(1))
+                .arg("-d")
+                .arg(&self.java_work_dir)
+                .arg(&self.main_file_path),
+            Some("compiler"),
+        )?;
 
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
@@ -122,27 +124,26 @@ impl Interpreter for Java_original {
 
     fn execute(&mut self) -> Result<String, SniprunError> {
         //run th binary and get the std output (or stderr)
-        let output = Command::new("java")
-            .arg("-cp")
-            .arg(&self.java_work_dir)
-            .arg(&self.bin_name)
-            .output()
-            .expect("Unable to start process");
+        let output = Java_original::run_command(
+            &self.data,
+            Command::new("java")
+                .arg("-cp")
+                .arg(&self.java_work_dir)
+                .arg(&self.bin_name),
+            None,
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Java_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
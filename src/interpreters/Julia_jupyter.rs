@@ -99,25 +99,23 @@ impl Interpreter for Julia_jupyter {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new("julia")
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Julia_jupyter::run_command(
+            &self.data,
+            Command::new("julia").arg(&self.main_file_path),
+            None,
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Julia_jupyter::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next_back()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -141,6 +139,8 @@ impl ReplLikeInterpreter for Julia_jupyter {
     }
 
     fn build_repl(&mut self) -> Result<(), SniprunError> {
+        // run through a shell script, that would fail silently
+        Julia_jupyter::check_executable(&self.data, "jupyter-console", None)?;
         let actual_command = String::from("echo")
             + " "
             + &String::from("'include(\"")
@@ -172,33 +172,36 @@ impl ReplLikeInterpreter for Julia_jupyter {
 
     fn execute_repl(&mut self) -> Result<String, SniprunError> {
         info!("starting executing repl: bash {}", &self.launcher_path);
-        let output = Command::new("bash")
-            .arg(&self.launcher_path)
-            .output()
-            .expect("failed to run command");
+        let output = Julia_jupyter::run_command(
+            &self.data,
+            Command::new("bash").arg(&self.launcher_path),
+            None,
+        )?;
         info!(
             "executed command!, stdout = {:?}, stder = {:?}",
             output.stdout, output.stderr
         );
-        let result = String::from_utf8(output.stdout).unwrap();
+        let result = decode_output(&output.stdout);
         let mut cleaned_result: Vec<_> = result.lines().collect();
         info!("collected result");
 
-        // first and last lines are the [In] x: prompts from jupyter-console
-        cleaned_result.remove(cleaned_result.len() - 1);
-        cleaned_result.remove(1);
-        cleaned_result.remove(0);
+        if cleaned_result.len() >= 3 {
+            // first and last lines are the [In] x: prompts from jupyter-console
+            cleaned_result.remove(cleaned_result.len() - 1);
+            cleaned_result.remove(1);
+            cleaned_result.remove(0);
+        }
         info!("result: {:?}", cleaned_result);
 
         info!("cleaned result: {:?}", cleaned_result);
-        if String::from_utf8(output.stderr.clone()).unwrap().is_empty() {
+        if decode_output(&output.stderr).is_empty() {
             Ok(cleaned_result.join("\n") + "\n")
         } else {
             Err(SniprunError::RuntimeError(
-                strip_ansi_escapes::strip_str(String::from_utf8_lossy(&output.stderr.clone()))
+                strip_ansi_escapes::strip_str(decode_output(&output.stderr))
                     .lines()
                     .last()
-                    .unwrap_or(&strip_ansi_escapes::strip_str(String::from_utf8_lossy(
+                    .unwrap_or(&strip_ansi_escapes::strip_str(decode_output(
                         &output.stderr,
                     )))
                     .to_owned(),
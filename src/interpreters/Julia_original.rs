@@ -14,6 +14,7 @@ pub struct Julia_original {
     interpreter_args: Vec<String>, // for now, used for --project=....
 
     current_output_id: u32,
+    image_dir: Option<String>,
 }
 
 impl Julia_original {
@@ -29,13 +30,18 @@ impl Julia_original {
         let mut contents = String::new();
 
         let mut pause = std::time::Duration::from_millis(50);
+        let start = std::time::Instant::now();
         loop {
             std::thread::sleep(pause);
             pause = pause.saturating_add(std::time::Duration::from_millis(50));
 
+            if start.elapsed().as_secs() > Julia_original::get_repl_timeout(&self.data) {
+                return Err(String::from("reached the repl timeout"));
+            }
+
             if let Ok(mut file) = std::fs::File::open(&path) {
                 info!("file exists");
-                let res = file.read_to_string(&mut contents);
+                let res = read_output(&mut file, &mut contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", contents);
                     // info!("file : {:?}", contents);
@@ -53,6 +59,20 @@ impl Julia_original {
         Ok(contents[index + start_mark.len()..contents.len() - end_mark.len() - 1].to_owned())
     }
 
+    /// run GR headless, and save the current Plots.jl plot (if any) to the image directory:
+    /// Plots.jl only keeps track of the last plot, so the previous ones are not saved
+    fn wrap_image_capture(&mut self) {
+        if let Some(image_dir) = &self.image_dir {
+            self.code = String::from("ENV[\"GKSwstype\"] = \"100\"\n")
+                + &self.code
+                + "\ntry\n    if isdefined(Main, :Plots)\n        sniprun_plot = Main.Plots.current()\n        mkpath("
+                + &Julia_original::string_literal(image_dir)
+                + ")\n        Main.Plots.savefig(sniprun_plot, joinpath("
+                + &Julia_original::string_literal(image_dir)
+                + ", \"figure_1.png\"))\n    end\ncatch\nend\n";
+        }
+    }
+
     fn fetch_config(&mut self) {
         let default_interpreter = String::from("julia");
         self.interpreter = default_interpreter;
@@ -96,6 +116,7 @@ impl Interpreter for Julia_original {
         //pre-create string pointing to main file's and binary's path
         let mfp = rwd.clone() + "/main.jl";
 
+        let image_dir = Julia_original::get_image_dir(&data);
         Box::new(Julia_original {
             data,
             support_level: level,
@@ -105,6 +126,7 @@ impl Interpreter for Julia_original {
             interpreter_args: Vec::new(),
             cache_dir: rwd,
             current_output_id: 0,
+            image_dir,
         })
     }
 
@@ -174,6 +196,7 @@ impl Interpreter for Julia_original {
         Ok(())
     }
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.wrap_image_capture();
         Ok(())
     }
     fn build(&mut self) -> Result<(), SniprunError> {
@@ -183,28 +206,27 @@ impl Interpreter for Julia_original {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new(self.interpreter.split_whitespace().next().unwrap())
-            .args(self.interpreter.split_whitespace().skip(1))
-            .args(&self.interpreter_args)
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = Julia_original::run_command(
+            &self.data,
+            Command::new(self.interpreter.split_whitespace().next().unwrap())
+                .args(self.interpreter.split_whitespace().skip(1))
+                .args(&self.interpreter_args)
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout) + &Julia_original::report_images(&self.image_dir))
         } else if Julia_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -238,6 +260,7 @@ impl ReplLikeInterpreter for Julia_original {
                 init_repl_cmd, &self.cache_dir
             );
 
+            Julia_original::check_executable(&self.data, &self.interpreter, Some("interpreter"))?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
@@ -304,7 +327,7 @@ impl ReplLikeInterpreter for Julia_original {
         let outfile = self.cache_dir.clone() + "/fifo_repl/out_file";
         info!("outfile : {:?}", outfile);
         match self.wait_out_file(outfile, self.current_output_id) {
-            Ok(s) => Ok(s),
+            Ok(s) => Ok(s + &Julia_original::report_images(&self.image_dir)),
             Err(s) => Err(SniprunError::CustomError(s)),
         }
     }
@@ -0,0 +1,367 @@
+#![allow(clippy::zombie_processes)]
+use crate::interpreters::import::*;
+
+#[derive(Clone)]
+#[allow(non_camel_case_types)]
+pub struct Jupyter_generic {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+    kernel_name: Option<String>,
+    kernel_file: String,
+    attached: bool,
+    main_file_path: String,
+}
+
+impl Jupyter_generic {
+    /// returns the (filetype, kernelspec name) pairs configured under the 'kernels' key
+    fn get_configured_kernels(data: &DataHolder) -> Vec<(String, String)> {
+        let mut kernels = vec![];
+        if let Some(kernels_config) = Jupyter_generic::get_interpreter_option(data, "kernels") {
+            if let Some(kernels_map) = kernels_config.as_map() {
+                for (filetype, kernel) in kernels_map.iter() {
+                    if let (Some(filetype_str), Some(kernel_str)) =
+                        (filetype.as_str(), kernel.as_str())
+                    {
+                        kernels.push((filetype_str.to_string(), kernel_str.to_string()));
+                    }
+                }
+            } else {
+                info!("Jupyter_generic's 'kernels' option should be a map filetype -> kernel name");
+            }
+        }
+        kernels
+    }
+
+    /// returns the (filetype, connection file) pairs of user-managed kernels,
+    /// configured under the 'connection_files' key
+    fn get_connection_files(data: &DataHolder) -> Vec<(String, String)> {
+        let mut files = vec![];
+        if let Some(files_config) =
+            Jupyter_generic::get_interpreter_option(data, "connection_files")
+        {
+            if let Some(files_map) = files_config.as_map() {
+                for (filetype, file) in files_map.iter() {
+                    if let (Some(filetype_str), Some(file_str)) = (filetype.as_str(), file.as_str())
+                    {
+                        if !file_str.is_empty() {
+                            files.push((filetype_str.to_string(), file_str.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+        files
+    }
+
+    pub fn get_configured_filetypes(data: &DataHolder) -> Vec<String> {
+        let mut filetypes: Vec<String> = Jupyter_generic::get_configured_kernels(data)
+            .into_iter()
+            .chain(Jupyter_generic::get_connection_files(data))
+            .map(|(filetype, _)| filetype)
+            .collect();
+        filetypes.sort();
+        filetypes.dedup();
+        info!(
+            "supported filetypes for Jupyter_generic are {:?}",
+            filetypes
+        );
+        filetypes
+    }
+
+    fn wait_on_kernel(&self) -> Result<(), SniprunError> {
+        let step = std::time::Duration::from_millis(100);
+        let start = std::time::Instant::now();
+        loop {
+            if let Ok(content) = std::fs::read_to_string(&self.kernel_file) {
+                if !content.is_empty() {
+                    return Ok(());
+                }
+            }
+            if start.elapsed().as_secs() > Jupyter_generic::get_repl_timeout(&self.data) {
+                return Err(SniprunError::CustomError(String::from(
+                    "Timeout on jupyter kernel start expired",
+                )));
+            }
+            std::thread::sleep(step);
+        }
+    }
+}
+
+impl Interpreter for Jupyter_generic {
+    fn new_with_level(data: DataHolder, level: SupportLevel) -> Box<Jupyter_generic> {
+        //create a subfolder in the cache folder, unique to this neovim instance
+        let pwd =
+            data.work_dir.clone() + "/jupyter_generic/" + &Jupyter_generic::get_nvim_pid(&data);
+        let mut builder = DirBuilder::new();
+        builder.recursive(true);
+        builder
+            .create(&pwd)
+            .expect("Could not create directory for jupyter-generic");
+
+        let kernel_name = Jupyter_generic::get_configured_kernels(&data)
+            .into_iter()
+            .find(|(filetype, _)| filetype == &data.filetype)
+            .map(|(_, kernel)| kernel);
+
+        // one kernel (and connection file) per filetype, unless attached to an existing one
+        let connection_file = Jupyter_generic::get_connection_files(&data)
+            .into_iter()
+            .find(|(filetype, _)| filetype == &data.filetype)
+            .map(|(_, file)| file);
+        let attached = connection_file.is_some();
+        let kp = connection_file.unwrap_or(pwd.clone() + "/kernel_" + &data.filetype + ".json");
+        let mfp = pwd + "/main_" + &data.filetype;
+        Box::new(Jupyter_generic {
+            data,
+            support_level: level,
+            code: String::new(),
+            kernel_name,
+            kernel_file: kp,
+            attached,
+            main_file_path: mfp,
+        })
+    }
+
+    fn get_name() -> String {
+        String::from("Jupyter_generic")
+    }
+
+    fn behave_repl_like_default() -> bool {
+        true
+    }
+
+    fn has_repl_capability() -> bool {
+        true
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        // actual filetypes are configured through the 'kernels' interpreter option
+        vec![String::from("Any (jupyter kernel)")]
+    }
+
+    fn get_current_level(&self) -> SupportLevel {
+        self.support_level
+    }
+    fn set_current_level(&mut self, level: SupportLevel) {
+        self.support_level = level;
+    }
+
+    fn get_data(&self) -> DataHolder {
+        self.data.clone()
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        if !self
+            .data
+            .current_bloc
+            .replace(&[' ', '\t', '\n', '\r'][..], "")
+            .is_empty()
+            && self.get_current_level() >= SupportLevel::Bloc
+        {
+            self.code.clone_from(&self.data.current_bloc);
+        } else if !self.data.current_line.replace(' ', "").is_empty()
+            && self.get_current_level() >= SupportLevel::Line
+        {
+            self.code.clone_from(&self.data.current_line);
+        } else {
+            self.code = String::from("");
+        }
+
+        if self.kernel_name.is_none() && !self.attached {
+            return Err(SniprunError::CustomError(format!(
+                "No jupyter kernel configured for filetype '{}' in Jupyter_generic's 'kernels' option",
+                self.data.filetype
+            )));
+        }
+        Ok(())
+    }
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.code = unindent(&format!("{}{}", "\n", self.code.as_str()));
+        Ok(())
+    }
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write(&self.main_file_path, &self.code)
+            .expect("Unable to write to file for jupyter_generic");
+        Ok(())
+    }
+    fn execute(&mut self) -> Result<String, SniprunError> {
+        Err(SniprunError::InterpreterLimitationError(
+            "Jupyter_generic only works in REPL mode, please enable it".to_owned(),
+        ))
+    }
+}
+
+impl ReplLikeInterpreter for Jupyter_generic {
+    fn fetch_code_repl(&mut self) -> Result<(), SniprunError> {
+        self.fetch_code()?;
+        if self.attached {
+            // the kernel is managed by the user (eg: a notebook), never start/remove it
+            if !std::path::Path::new(&self.kernel_file).exists() {
+                return Err(SniprunError::CustomError(format!(
+                    "Jupyter connection file not found: {}",
+                    self.kernel_file
+                )));
+            }
+            info!(
+                "Attached to existing jupyter kernel at {}",
+                self.kernel_file
+            );
+            return Ok(());
+        }
+        let kernel_name = self.kernel_name.clone().unwrap_or_default();
+
+        // sniprun memory holds one 'filetype=kernel' line per running kernel
+        let kernel_marker = self.data.filetype.clone() + "=" + &kernel_name;
+        let saved_code = self.read_previous_code();
+        let kernel_running = saved_code.lines().any(|l| l == kernel_marker)
+            && std::path::Path::new(&self.kernel_file).exists();
+
+        if !kernel_running {
+            //initialize kernel. Relying on self.read_previous_code to
+            //know when to start a new kernel is important as
+            //this will be cleared by the SnipReplMemoryClean command
+            let _res = std::fs::remove_file(&self.kernel_file);
+            let _kernel = Jupyter_generic::spawn_command(
+                &self.data,
+                Command::new("jupyter-kernel")
+                    .arg(String::from("--kernel=") + &kernel_name)
+                    .arg(String::from("--KernelManager.connection_file=") + &self.kernel_file),
+                None,
+            )?;
+            info!("Initialized kernel {}", kernel_name);
+            self.save_code(kernel_marker);
+        } else {
+            info!(
+                "Using already loaded jupyter kernel at {}",
+                self.kernel_file
+            );
+        }
+        Ok(())
+    }
+
+    fn add_boilerplate_repl(&mut self) -> Result<(), SniprunError> {
+        self.add_boilerplate()
+    }
+
+    fn build_repl(&mut self) -> Result<(), SniprunError> {
+        self.build()
+    }
+
+    fn execute_repl(&mut self) -> Result<String, SniprunError> {
+        self.wait_on_kernel()?;
+
+        let input =
+            File::open(&self.main_file_path).expect("Unable to read main file for jupyter_generic");
+        let output = Jupyter_generic::run_command(
+            &self.data,
+            Command::new("jupyter-console")
+                .arg("--existing")
+                .arg(&self.kernel_file)
+                .arg("--simple-prompt")
+                .arg("-y")
+                .arg("--no-confirm")
+                .arg("--ZMQTerminalInteractiveShell.banner=\"\"")
+                .stdin(input),
+            None,
+        )?;
+
+        let result = strip_ansi_escapes::strip_str(decode_output(&output.stdout));
+        info!("result: {:?}", result);
+
+        // remove the In [x]: / Out[x]: prompts from jupyter-console
+        let prompt = Regex::new(r"^\s*(In|Out)\s*\[\d+\]:\s?").unwrap();
+        let cleaned_result: Vec<_> = result
+            .lines()
+            .map(|l| prompt.replace(l, "").to_string())
+            .filter(|l| !l.trim().is_empty())
+            .collect();
+        info!("cleaned result: {:?}", cleaned_result);
+
+        let stderr = strip_ansi_escapes::strip_str(decode_output(&output.stderr));
+        if stderr.trim().is_empty() {
+            Ok(cleaned_result.join("\n") + "\n")
+        } else if Jupyter_generic::error_truncate(&self.get_data()) == ErrTruncate::Short {
+            Err(SniprunError::RuntimeError(
+                stderr
+                    .lines()
+                    .rfind(|l| !l.trim().is_empty())
+                    .unwrap_or(&stderr)
+                    .to_owned(),
+            ))
+        } else {
+            Err(SniprunError::RuntimeError(stderr))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_jupyter_generic {
+    use super::*;
+    use neovim_lib::Value;
+
+    fn data_with_kernels() -> DataHolder {
+        let kernels = Value::from(vec![
+            (Value::from("r"), Value::from("ir")),
+            (Value::from("haskell"), Value::from("ihaskell")),
+        ]);
+        let options = Value::from(vec![(
+            Value::from("interpreter_options"),
+            Value::from(vec![(
+                Value::from("Jupyter_generic"),
+                Value::from(vec![(Value::from("kernels"), kernels)]),
+            )]),
+        )]);
+        let mut data = DataHolder::new();
+        data.interpreter_options = Some(options);
+        data
+    }
+
+    #[test]
+    fn configured_filetypes() {
+        let data = data_with_kernels();
+        assert_eq!(
+            Jupyter_generic::get_configured_filetypes(&data),
+            vec![String::from("haskell"), String::from("r")]
+        );
+    }
+
+    #[test]
+    fn unconfigured_filetype() {
+        let mut data = data_with_kernels();
+        data.filetype = String::from("ocaml");
+        data.current_bloc = String::from("print_int 1");
+        let mut interpreter = Jupyter_generic::new(data);
+        assert!(interpreter.fetch_code().is_err());
+    }
+
+    #[test]
+    fn attached_kernel_missing() {
+        let mut data = data_with_kernels();
+        data.filetype = String::from("r");
+        data.current_bloc = String::from("print(1)");
+        data.interpreter_options = Some(Value::from(vec![(
+            Value::from("interpreter_options"),
+            Value::from(vec![(
+                Value::from("Jupyter_generic"),
+                Value::from(vec![(
+                    Value::from("connection_files"),
+                    Value::from(vec![(
+                        Value::from("r"),
+                        Value::from("/nonexistent/kernel-1234.json"),
+                    )]),
+                )]),
+            )]),
+        )]));
+        assert_eq!(
+            Jupyter_generic::get_configured_filetypes(&data),
+            vec![String::from("r")]
+        );
+        let mut interpreter = Jupyter_generic::new(data);
+        assert!(interpreter.fetch_code_repl().is_err());
+    }
+}
@@ -119,30 +119,29 @@ impl Interpreter for Lua_nvim {
 
     fn execute(&mut self) -> Result<String, SniprunError> {
         let interpreter = Lua_nvim::get_interpreter_or(&self.data, "nvim");
-        let output = Command::new(interpreter)
-            .arg("--headless")
-            .arg("-c")
-            .arg(format!("luafile {}", &self.main_file_path))
-            .arg("-c")
-            .arg("q!")
-            .output()
-            .expect("Unable to start process");
+        let output = Lua_nvim::run_command(
+            &self.data,
+            Command::new(interpreter)
+                .arg("--headless")
+                .arg("-c")
+                .arg(format!("luafile {}", &self.main_file_path))
+                .arg("-c")
+                .arg("q!"),
+            Some("interpreter"),
+        )?;
         info!("yay from lua interpreter - in another nvim instance");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Lua_nvim::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -54,13 +54,17 @@ impl Mathematica_original {
         let mut contents = String::new();
 
         let mut pause = std::time::Duration::from_millis(50);
-        let _start = std::time::Instant::now();
+        let start = std::time::Instant::now();
         loop {
             std::thread::sleep(pause);
             pause = pause.saturating_add(std::time::Duration::from_millis(50));
+
+            if start.elapsed().as_secs() > Mathematica_original::get_repl_timeout(&self.data) {
+                return Err(String::from("reached the repl timeout"));
+            }
             if let Ok(mut file) = std::fs::File::open(&path) {
                 info!("file exists");
-                let res = file.read_to_string(&mut contents);
+                let res = read_output(&mut file, &mut contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", contents);
                     // info!("file : {:?}", contents);
@@ -247,22 +251,22 @@ impl Interpreter for Mathematica_original {
     fn execute(&mut self) -> Result<String, SniprunError> {
         //run th binary and get the std output (or stderr)
         let interpreter = Mathematica_original::get_interpreter_or(&self.data, "WolframKernel");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg("-noprompt")
-            .arg("-script")
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Mathematica_original::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg("-noprompt")
+                .arg("-script")
+                .arg(&self.main_file_path),
+            Some("interpreter"),
+        )?;
 
         if output.status.success() {
             //return stdout
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else {
             // return stderr
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -296,6 +300,7 @@ impl ReplLikeInterpreter for Mathematica_original {
                 init_repl_cmd, &self.language_work_dir
             );
 
+            Mathematica_original::check_executable(&self.data, "WolframKernel", None)?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
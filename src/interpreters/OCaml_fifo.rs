@@ -52,7 +52,7 @@ impl OCaml_fifo {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -193,17 +193,18 @@ impl Interpreter for OCaml_fifo {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new(self.interpreter.clone())
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = OCaml_fifo::run_command(
+            &self.data,
+            Command::new(self.interpreter.clone())
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if OCaml_fifo::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr)
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .filter(|l| !l.to_lowercase().contains("error"))
                     .take(2)
@@ -211,9 +212,7 @@ impl Interpreter for OCaml_fifo {
                     .join("\n"),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -250,6 +249,11 @@ impl ReplLikeInterpreter for OCaml_fifo {
                 init_repl_cmd, &self.cache_dir, self.interpreter_repl
             );
 
+            OCaml_fifo::check_executable(
+                &self.data,
+                &self.interpreter_repl,
+                Some("interpreter_repl"),
+            )?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
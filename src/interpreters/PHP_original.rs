@@ -49,7 +49,7 @@ impl PHP_original {
             if let Ok(mut file) = std::fs::File::open(&err_path) {
                 info!("errfile exists");
                 err_contents.clear();
-                if file.read_to_string(&mut err_contents).is_ok() {
+                if read_output(&mut file, &mut err_contents).is_ok() {
                     // info!("errfile could be read : {:?}", err_contents);
                     if let Some(end_index) = err_contents.rfind(&end_mark_nl) {
                         if let Some(index) = err_contents.rfind(&start_mark) {
@@ -69,7 +69,7 @@ impl PHP_original {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                if file.read_to_string(&mut out_contents).is_ok() {
+                if read_output(&mut file, &mut out_contents).is_ok() {
                     // info!("file could be read : {:?}", out_contents);
                     if out_contents.contains(&end_mark) {
                         info!("out found");
@@ -201,26 +201,25 @@ impl Interpreter for PHP_original {
             "Executing PHP_original with interpreter: {:?}",
             self.interpreter
         );
-        let output = Command::new(&self.interpreter)
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = PHP_original::run_command(
+            &self.data,
+            Command::new(&self.interpreter)
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if PHP_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -256,6 +255,7 @@ impl ReplLikeInterpreter for PHP_original {
                 init_repl_cmd, &self.cache_dir
             );
 
+            PHP_original::check_executable(&self.data, &self.interpreter, Some("interpreter"))?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
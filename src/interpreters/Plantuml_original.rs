@@ -201,25 +201,27 @@ impl Interpreter for Plantuml_original {
 
         let compiler = Plantuml_original::get_compiler_or(&self.data, "plantuml");
         //compile it (to the bin_path that already points to the rigth path)
-        let output = Command::new(compiler.split_whitespace().next().unwrap())
-            .args(compiler.split_whitespace().skip(1))
-            .arg("-o")
-            .arg(&self.language_work_dir)
-            .arg(&self.output_mode)
-            .arg("-nbthread")
-            .arg("auto")
-            .arg("-failfast2")
-            .arg(self.main_file_path.clone())
-            .output()
-            .expect("Unable to start process");
+        let output = Plantuml_original::run_command(
+            &self.data,
+            Command::new(compiler.split_whitespace().next().unwrap())
+                .args(compiler.split_whitespace().skip(1))
+                .arg("-o")
+                .arg(&self.language_work_dir)
+                .arg(&self.output_mode)
+                .arg("-nbthread")
+                .arg("auto")
+                .arg("-failfast2")
+                .arg(self.main_file_path.clone()),
+            Some("compiler"),
+        )?;
         if output.status.success() {
             //return stdout
             Ok(())
         } else {
             // return stderr
-            Err(SniprunError::CompilationError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::CompilationError(decode_output(
+                &output.stderr,
+            )))
         }
     }
 
@@ -94,36 +94,37 @@ impl Interpreter for Prolog_original {
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
         let output = if self.interpreter != "gprolog" {
-            Command::new(self.interpreter.clone())
-                .arg(&self.main_file_path)
-                .args(&self.get_data().cli_args)
-                .output()
-                .expect("Unable to start process")
+            Prolog_original::run_command(
+                &self.data,
+                Command::new(self.interpreter.clone())
+                    .arg(&self.main_file_path)
+                    .args(&self.get_data().cli_args),
+                Some("interpreter"),
+            )?
         } else {
             // special case for gprolog which needs the --consult-file arg
-            Command::new("gprolog")
-                .arg(String::from("--consult-file"))
-                .arg(&self.main_file_path)
-                .args(&self.get_data().cli_args)
-                .output()
-                .expect("Unable to start process")
+            Prolog_original::run_command(
+                &self.data,
+                Command::new("gprolog")
+                    .arg(String::from("--consult-file"))
+                    .arg(&self.main_file_path)
+                    .args(&self.get_data().cli_args),
+                Some("interpreter"),
+            )?
         };
         info!("yay from Prolog interpreter");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Prolog_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ));
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::RuntimeError(decode_output(&output.stderr)));
         }
     }
 }
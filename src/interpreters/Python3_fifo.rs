@@ -14,6 +14,7 @@ pub struct Python3_fifo {
     interpreter: String,
     venv: Option<String>,
     current_output_id: u32,
+    image_dir: Option<String>,
 }
 
 impl Python3_fifo {
@@ -64,7 +65,7 @@ impl Python3_fifo {
             if let Ok(mut file) = std::fs::File::open(&err_path) {
                 info!("errfile exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 if res.is_ok() {
                     info!("errfile could be read : {:?}", err_contents);
                     // info!("file : {:?}", contents);
@@ -94,7 +95,7 @@ impl Python3_fifo {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -207,6 +208,10 @@ impl Python3_fifo {
 
     /// needs imports to have been fetched already
     fn unblock_plot(&mut self) {
+        if self.image_dir.is_some() {
+            // figures are saved to files, and plt.show() is a no-op with the Agg backend
+            return;
+        }
         let all_imports = self.imports.clone() + &self.read_previous_code();
 
         //it's not really pretty but should work most of the time
@@ -223,6 +228,22 @@ impl Python3_fifo {
             .replace("pyplot.show()", "pyplot.show(block=False)");
     }
 
+    /// python code installing a displayhook that outputs pandas objects
+    /// as table blocks (see crate::table), if the 'table_output' option is set
+    fn table_output_code(&self) -> String {
+        let table_output = Python3_fifo::get_interpreter_option(&self.get_data(), "table_output")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if !table_output {
+            return String::new();
+        }
+        format!(
+            "\ndef sniprun_displayhook(value):\n\tsniprun_is_table = type(value).__module__.startswith(\"pandas\") and hasattr(value, \"to_csv\")\n\tprint(\"{}\\n\" + value.to_csv(sep=\"\\t\") + \"{}\") if sniprun_is_table else sys.__displayhook__(value)\n\nsys.displayhook = sniprun_displayhook\n\n",
+            crate::table::TABLE_START,
+            crate::table::TABLE_END
+        )
+    }
+
     fn fetch_config(&mut self) {
         let default_interpreter = String::from("python3");
         self.interpreter = default_interpreter;
@@ -271,6 +292,7 @@ impl Interpreter for Python3_fifo {
         //pre-create string pointing to main file's and binary's path
         let mfp = rwd.clone() + "/main.py";
 
+        let image_dir = Python3_fifo::get_image_dir(&data);
         Box::new(Python3_fifo {
             cache_dir: rwd + "/" + &Python3_fifo::get_nvim_pid(&data),
             data,
@@ -281,6 +303,7 @@ impl Interpreter for Python3_fifo {
             current_output_id: 0,
             interpreter: String::new(),
             venv: None,
+            image_dir,
         })
     }
 
@@ -364,10 +387,17 @@ impl Interpreter for Python3_fifo {
             source_venv += "\nexec(compile(open(activate_this_file, \"rb\").read(), activate_this_file, 'exec'), dict(__file__=activate_this_file))\n";
         }
 
+        let (capture_pre, capture_post) =
+            crate::interpreters::Python3_original::Python3_original::image_capture_code(
+                &self.image_dir,
+            );
+        // after the imports, that may have to come first (ex: 'from __future__ import ...')
         self.code = source_venv
             + &self.imports.clone()
+            + &capture_pre
             + "\n## Imports above, code below, a #\\n# marker is very important to separate the try/catch bloc from the code  ##Here it is: #\n#"
-            + &unindent(&format!("{}{}", "\n\n", self.code.as_str()));
+            + &unindent(&format!("{}{}", "\n\n", self.code.as_str()))
+            + &capture_post;
         info!("source code::::: {}", self.code);
         Ok(())
     }
@@ -414,6 +444,7 @@ impl ReplLikeInterpreter for Python3_fifo {
                 init_repl_cmd, &self.cache_dir
             );
 
+            Python3_fifo::check_executable(&self.data, &self.interpreter, Some("interpreter"))?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
@@ -495,6 +526,7 @@ impl ReplLikeInterpreter for Python3_fifo {
 
         let all_code = String::from("\n") + &self.code + "\n\n";
         self.code = String::from("\nimport sys\n\n")
+            + &self.table_output_code()
             + &run_ion
             + &start_mark
             + &start_mark_err
@@ -524,6 +556,7 @@ impl ReplLikeInterpreter for Python3_fifo {
         let errfile = self.cache_dir.clone() + "/fifo_repl/err_file";
         info!("outfile : {:?}", outfile);
         self.wait_out_file(outfile, errfile, self.current_output_id)
+            .map(|out| out + &Python3_fifo::report_images(&self.image_dir))
     }
 }
 
@@ -533,6 +566,26 @@ mod test_python3_fifo {
 
     use serial_test::serial;
 
+    #[test]
+    #[serial(pythonfifo)]
+    fn non_utf8_output() {
+        let interpreter = Python3_fifo::new(DataHolder::new());
+        let dir = std::env::temp_dir().join("sniprun_test_python3_fifo");
+        std::fs::create_dir_all(&dir).unwrap();
+        let out_path = dir.join("out_file");
+        std::fs::write(
+            &out_path,
+            b"sniprun_started_id=1caf\xe9\nsniprun_finished_id=1\n",
+        )
+        .unwrap();
+        let res = interpreter.wait_out_file(
+            out_path.to_string_lossy().into_owned(),
+            dir.join("err_file").to_string_lossy().into_owned(),
+            1,
+        );
+        assert_eq!(res, Ok(String::from("caf\u{fffd}")));
+    }
+
     #[test]
     #[serial(pythonfifo)]
     fn simple_print() {
@@ -9,6 +9,7 @@ pub struct Python3_jupyter {
     code: String,
     imports: String,
     kernel_file: String,
+    attached: bool,
     main_file_path: String,
     launcher_path: String,
 }
@@ -124,13 +125,19 @@ impl Interpreter for Python3_jupyter {
         let mfp = pwd.clone() + "/main.py";
         let lp = pwd.clone() + "/main.sh";
 
-        let kp = pwd + "/kernel_sniprun.json";
+        // attach to an user-provided kernel if a connection file is configured
+        let connection_file = Python3_jupyter::get_interpreter_option(&data, "connection_file")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .filter(|s| !s.is_empty());
+        let attached = connection_file.is_some();
+        let kp = connection_file.unwrap_or(pwd + "/kernel_sniprun.json");
         Box::new(Python3_jupyter {
             data,
             support_level: level,
             code: String::new(),
             imports: String::new(),
             kernel_file: kp,
+            attached,
             main_file_path: mfp,
             launcher_path: lp,
         })
@@ -214,31 +221,45 @@ impl Interpreter for Python3_jupyter {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new("python3")
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Python3_jupyter::run_command(
+            &self.data,
+            Command::new("python3").arg(&self.main_file_path),
+            None,
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Python3_jupyter::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
 impl ReplLikeInterpreter for Python3_jupyter {
     fn fetch_code_repl(&mut self) -> Result<(), SniprunError> {
         self.fetch_code()?;
+        if self.attached {
+            // the kernel is managed by the user (eg: a notebook), never start/remove it
+            if !std::path::Path::new(&self.kernel_file).exists() {
+                return Err(SniprunError::CustomError(format!(
+                    "Jupyter connection file not found: {}",
+                    self.kernel_file
+                )));
+            }
+            info!(
+                "Attached to existing jupyter kernel at {}",
+                self.kernel_file
+            );
+            // sniprun's memory tracks the kernel sniprun started (and its imports):
+            // left untouched, and all the imports are sent to the attached kernel
+            return Ok(());
+        }
         let saved_code = self.read_previous_code();
         let mut saved_code: Vec<_> = saved_code.lines().collect();
         if saved_code.is_empty() {
@@ -246,10 +267,13 @@ impl ReplLikeInterpreter for Python3_jupyter {
             //know when to start a new kernel is important as
             //this will be cleared by the SnipReplMemoryClean command
             let _res = std::fs::remove_file(&self.kernel_file);
-            let _res = Command::new("jupyter-kernel")
-                .arg("--kernel=python3")
-                .arg(String::from("--KernelManager.connection_file=") + &self.kernel_file)
-                .spawn();
+            let _kernel = Python3_jupyter::spawn_command(
+                &self.data,
+                Command::new("jupyter-kernel")
+                    .arg("--kernel=python3")
+                    .arg(String::from("--KernelManager.connection_file=") + &self.kernel_file),
+                None,
+            )?;
             info!("Initialized kernel");
         } else {
             // kernel already running
@@ -297,6 +321,8 @@ impl ReplLikeInterpreter for Python3_jupyter {
     }
 
     fn build_repl(&mut self) -> Result<(), SniprunError> {
+        // run through a shell script, that would fail silently
+        Python3_jupyter::check_executable(&self.data, "jupyter-console", None)?;
         let actual_command = String::from("echo")
             + " "
             + &String::from("'exec(open(\"")
@@ -335,11 +361,12 @@ impl ReplLikeInterpreter for Python3_jupyter {
         );
         self.wait_on_kernel()?;
 
-        let output = Command::new("sh")
-            .arg(&self.launcher_path)
-            .output()
-            .expect("Unable to start process");
-        let result = String::from_utf8(output.stdout).unwrap();
+        let output = Python3_jupyter::run_command(
+            &self.data,
+            Command::new("sh").arg(&self.launcher_path),
+            None,
+        )?;
+        let result = decode_output(&output.stdout);
         let mut cleaned_result: Vec<_> = result.lines().collect();
 
         info!("result: {:?}", cleaned_result);
@@ -352,14 +379,14 @@ impl ReplLikeInterpreter for Python3_jupyter {
         }
 
         info!("cleaned result: {:?}", cleaned_result);
-        if String::from_utf8(output.stderr.clone()).unwrap().is_empty() {
+        if decode_output(&output.stderr).is_empty() {
             Ok(cleaned_result.join("\n") + "\n")
         } else {
             Err(SniprunError::RuntimeError(
-                strip_ansi_escapes::strip_str(String::from_utf8_lossy(&output.stderr.clone()))
+                strip_ansi_escapes::strip_str(decode_output(&output.stderr))
                     .lines()
                     .last()
-                    .unwrap_or(&strip_ansi_escapes::strip_str(String::from_utf8_lossy(
+                    .unwrap_or(&strip_ansi_escapes::strip_str(decode_output(
                         &output.stderr,
                     )))
                     .to_owned(),
@@ -386,6 +413,40 @@ mod test_python3_jupyter {
         assert!(string_result.contains("a 1"));
     }
 
+    #[test]
+    fn attached_keeps_memory() {
+        use neovim_lib::Value;
+        use std::sync::{Arc, Mutex};
+        let connection_file = std::env::temp_dir().join("sniprun_test_kernel.json");
+        std::fs::write(&connection_file, "{}").unwrap();
+        // the memory of the kernel started by sniprun before attaching
+        let memory = Arc::new(Mutex::new(InterpreterData {
+            owner: Python3_jupyter::get_name(),
+            content: String::from("\nimport os"),
+            pid: None,
+        }));
+        let mut data = DataHolder::new();
+        data.interpreter_data = Some(memory.clone());
+        data.current_bloc = String::from("print(1)");
+        data.interpreter_options = Some(Value::from(vec![(
+            Value::from("interpreter_options"),
+            Value::from(vec![(
+                Value::from("Python3_jupyter"),
+                Value::from(vec![(
+                    Value::from("connection_file"),
+                    Value::from(connection_file.to_string_lossy().as_ref()),
+                )]),
+            )]),
+        )]));
+        // imports fetched from the buffer (which needs neovim)
+        let mut interpreter = Python3_jupyter::new_with_level(data, SupportLevel::Bloc);
+        interpreter.imports = String::from("import os\nimport sys");
+        interpreter.fetch_code_repl().unwrap();
+        // the imports are all sent to the attached kernel, and not recorded
+        assert_eq!(interpreter.imports.lines().count(), 2);
+        assert_eq!(memory.lock().unwrap().content, "\nimport os");
+    }
+
     #[allow(dead_code)]
     fn test_repl() {
         let mut event_handler = fake_event();
@@ -12,6 +12,7 @@ pub struct Python3_original {
     plugin_root: String,
     cache_dir: String,
     venv: Option<String>,
+    image_dir: Option<String>,
 }
 impl Python3_original {
     fn fetch_imports(&mut self) -> Result<(), SniprunError> {
@@ -100,6 +101,24 @@ impl Python3_original {
         }
         false
     }
+    /// python code forcing a non-interactive matplotlib backend, and saving every figure
+    /// to the image directory, to respectively prepend and append to the snippet
+    /// (shared with Python3_fifo)
+    pub fn image_capture_code(image_dir: &Option<String>) -> (String, String) {
+        if let Some(image_dir) = image_dir {
+            let pre = String::from(
+                "\nimport warnings\nwarnings.filterwarnings(\"ignore\", message=\".*non-interactive.*\")\ntry:\n    import matplotlib\n    matplotlib.use(\"Agg\")\nexcept ImportError:\n    pass\n",
+            );
+            let post = format!(
+                "\ntry:\n    import matplotlib.pyplot as sniprun_plt\n    if sniprun_plt.get_fignums():\n        import os as sniprun_os\n        sniprun_os.makedirs({0}, exist_ok=True)\n    for sniprun_fig_num in sniprun_plt.get_fignums():\n        sniprun_plt.figure(sniprun_fig_num).savefig({0} + \"/figure_%d.png\" % sniprun_fig_num)\n    sniprun_plt.close(\"all\")\nexcept ImportError:\n    pass\n",
+                Python3_original::string_literal(image_dir)
+            );
+            (pre, post)
+        } else {
+            (String::new(), String::new())
+        }
+    }
+
     fn fetch_config(&mut self) {
         let default_interpreter = String::from("python3");
         self.interpreter = default_interpreter;
@@ -149,6 +168,7 @@ impl Interpreter for Python3_original {
         let mfp = rwd.clone() + "/main.py";
 
         let pgr = data.sniprun_root_dir.clone();
+        let image_dir = Python3_original::get_image_dir(&data);
         Box::new(Python3_original {
             data,
             support_level: level,
@@ -159,6 +179,7 @@ impl Interpreter for Python3_original {
             cache_dir: rwd,
             interpreter: String::new(),
             venv: None,
+            image_dir,
         })
     }
 
@@ -245,10 +266,14 @@ impl Interpreter for Python3_original {
             source_venv += "\nexec(compile(open(activate_this_file, \"rb\").read(), activate_this_file, 'exec'), dict(__file__=activate_this_file))\n";
         }
 
+        let (capture_pre, capture_post) = Python3_original::image_capture_code(&self.image_dir);
+        // after the imports, that may have to come first (ex: 'from __future__ import ...')
         self.code = source_venv
             + &self.imports.clone()
             + "\n"
-            + &unindent(&format!("{}{}", "\n", self.code.as_str()));
+            + &capture_pre
+            + &unindent(&format!("{}{}", "\n", self.code.as_str()))
+            + &capture_post;
         Ok(())
     }
     fn build(&mut self) -> Result<(), SniprunError> {
@@ -258,27 +283,26 @@ impl Interpreter for Python3_original {
         Ok(())
     }
     fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new(self.interpreter.split_whitespace().next().unwrap())
-            .args(self.interpreter.split_whitespace().skip(1))
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = Python3_original::run_command(
+            &self.data,
+            Command::new(self.interpreter.split_whitespace().next().unwrap())
+                .args(self.interpreter.split_whitespace().skip(1))
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout) + &Python3_original::report_images(&self.image_dir))
         } else if Python3_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -301,8 +325,10 @@ impl ReplLikeInterpreter for Python3_original {
         let python_functions = std::fs::read_to_string(&path_to_python_functions).unwrap();
         let klepto_memo = String::from("'") + &self.cache_dir.clone() + "/" + "memo" + "'";
 
+        let (capture_pre, capture_post) = Python3_original::image_capture_code(&self.image_dir);
         let mut final_code = self.imports.clone();
         final_code.push('\n');
+        final_code.push_str(&capture_pre);
         final_code.push_str(&python_functions);
         final_code.push('\n');
         if self.read_previous_code().is_empty() {
@@ -320,6 +346,7 @@ impl ReplLikeInterpreter for Python3_original {
 
         final_code.push_str(&unindent(&format!("{}{}", "\n", self.code.as_str())));
         final_code.push('\n');
+        final_code.push_str(&capture_post);
         {
             final_code.push_str("sniprun142859_save("); // if the run has not failed, save new variables
             final_code.push_str(&klepto_memo);
@@ -351,6 +378,55 @@ mod test_python3_original {
         assert_eq!(string_result, "lol 1\n");
     }
 
+    #[test]
+    fn image_capture() {
+        use neovim_lib::Value;
+        let mut data = DataHolder::new();
+        data.interpreter_options = Some(Value::from(vec![(
+            Value::from("interpreter_options"),
+            Value::from(vec![(
+                Value::from("Python3_original"),
+                Value::from(vec![(Value::from("image_capture"), Value::from(true))]),
+            )]),
+        )]));
+        data.current_bloc = String::from("print(\"lol\",1);");
+        let work_dir = std::env::temp_dir().join("sniprun_test_image_capture");
+        let _ = std::fs::remove_dir_all(&work_dir);
+        for i in 0..25 {
+            std::fs::create_dir_all(work_dir.join(format!("images/Python3_original_{}", i)))
+                .unwrap();
+        }
+        data.work_dir = work_dir.to_string_lossy().into_owned();
+        let mut interpreter = Python3_original::new(data);
+        assert!(interpreter.image_dir.is_some());
+        let res = interpreter.run_at_level(SupportLevel::Bloc);
+
+        // no figure was produced, so no image is reported (nor directory created)
+        let string_result = res.unwrap();
+        assert_eq!(string_result, "lol 1\n");
+        assert!(!std::path::Path::new(interpreter.image_dir.as_ref().unwrap()).exists());
+        // the oldest image directories were removed
+        let kept = std::fs::read_dir(work_dir.join("images")).unwrap().count();
+        assert_eq!(kept, 19);
+    }
+
+    #[test]
+    fn image_dir_literal() {
+        // the image directory is pasted in the generated code
+        let dir = "/tmp/it's a \"dir\"\\$HOME\n";
+        let (_, post) = Python3_original::image_capture_code(&Some(dir.to_string()));
+        assert!(post.contains(r#"savefig("/tmp/it's a \"dir\"\\\x24HOME\x0a" + "/figure_%d.png""#));
+        let output = std::process::Command::new("python3")
+            .arg("-c")
+            .arg(format!(
+                "import sys; sys.stdout.write({})",
+                Python3_original::string_literal(dir)
+            ))
+            .output()
+            .unwrap();
+        assert_eq!(decode_output(&output.stdout), dir);
+    }
+
     #[allow(dead_code)]
     fn test_repl() {
         let mut event_handler = fake_event();
@@ -9,7 +9,32 @@ pub struct R_original {
     code: String,
     r_work_dir: String,
     main_file_path: String,
+    image_dir: Option<String>,
 }
+
+impl R_original {
+    /// send the plots to png files, moved to the image directory (created if there are some)
+    /// once the snippet is done, instead of the default device
+    fn wrap_image_capture(&mut self) {
+        if let Some(image_dir) = &self.image_dir {
+            let image_dir = R_original::string_literal(image_dir);
+            self.code = String::from("png(file.path(tempdir(), 'sniprun_figure_%03d.png'))\n")
+                + &self.code
+                + "\ninvisible(dev.off())\n"
+                + "sniprun_figures <- list.files(tempdir(), pattern = '^sniprun_figure_.*png$', full.names = TRUE)\n"
+                + "if (length(sniprun_figures) > 0) {\n"
+                + "    dir.create("
+                + &image_dir
+                + ", recursive = TRUE, showWarnings = FALSE)\n"
+                + "    invisible(file.copy(sniprun_figures, file.path("
+                + &image_dir
+                + ", sub('^sniprun_', '', basename(sniprun_figures)))))\n"
+                + "    invisible(file.remove(sniprun_figures))\n"
+                + "}\n";
+        }
+    }
+}
+
 impl Interpreter for R_original {
     fn new_with_level(data: DataHolder, level: SupportLevel) -> Box<R_original> {
         let bwd = data.work_dir.clone() + "/R-original";
@@ -19,12 +44,14 @@ impl Interpreter for R_original {
             .create(&bwd)
             .expect("Could not create directory for R-original");
         let mfp = bwd.clone() + "/main.r";
+        let image_dir = R_original::get_image_dir(&data);
         Box::new(R_original {
             data,
             support_level: level,
             code: String::from(""),
             r_work_dir: bwd,
             main_file_path: mfp,
+            image_dir,
         })
     }
 
@@ -87,6 +114,7 @@ impl Interpreter for R_original {
     }
 
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.wrap_image_capture();
         Ok(())
     }
 
@@ -100,28 +128,27 @@ impl Interpreter for R_original {
 
     fn execute(&mut self) -> Result<String, SniprunError> {
         let interpreter = R_original::get_interpreter_or(&self.data, "Rscript");
-        let output = Command::new(interpreter.split_whitespace().next().unwrap())
-            .args(interpreter.split_whitespace().skip(1))
-            .arg(&self.main_file_path)
-            .args(&self.get_data().cli_args)
-            .output()
-            .expect("Unable to start process");
+        let output = R_original::run_command(
+            &self.data,
+            Command::new(interpreter.split_whitespace().next().unwrap())
+                .args(interpreter.split_whitespace().skip(1))
+                .arg(&self.main_file_path)
+                .args(&self.get_data().cli_args),
+            Some("interpreter"),
+        )?;
         info!("yay from R interpreter");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout) + &R_original::report_images(&self.image_dir))
         } else if R_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -138,6 +165,7 @@ impl ReplLikeInterpreter for R_original {
 
     fn add_boilerplate_repl(&mut self) -> Result<(), SniprunError> {
         info!("repl mode");
+        self.wrap_image_capture();
         let mut final_code = String::new();
 
         let rdata_path = self.r_work_dir.clone() + "/sniprun.RData";
@@ -67,7 +67,7 @@ impl Rust_original {
             if let Ok(mut file) = std::fs::File::open(err_path) {
                 info!("file exists");
                 err_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", err_contents);
                     // info!("file : {:?}", contents);
@@ -90,7 +90,7 @@ impl Rust_original {
             if let Ok(mut file) = std::fs::File::open(out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -217,17 +217,19 @@ impl Interpreter for Rust_original {
         write(&self.main_file_path, &self.code).expect("Unable to write to file for rust-original");
 
         //compile it (to the bin_path that arleady points to the rigth path)
-        let output = Command::new(self.compiler.split_whitespace().next().unwrap())
-            .args(self.compiler.split_whitespace().skip(1))
-            .arg("--out-dir")
-            .arg(&self.rust_work_dir)
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Rust_original::run_command(
+            &self.data,
+            Command::new(self.compiler.split_whitespace().next().unwrap())
+                .args(self.compiler.split_whitespace().skip(1))
+                .arg("--out-dir")
+                .arg(&self.rust_work_dir)
+                .arg(&self.main_file_path),
+            Some("compiler"),
+        )?;
 
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            let error_message = String::from_utf8(output.stderr).unwrap();
+            let error_message = decode_output(&output.stderr);
             //
             //take first line and remove first 'error' word (redondant)
             let first_line = error_message
@@ -249,20 +251,17 @@ impl Interpreter for Rust_original {
             .output()
             .expect("Unable to start process");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Rust_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
@@ -352,6 +351,7 @@ impl ReplLikeInterpreter for Rust_original {
                 init_repl_cmd, &self.cache_dir
             );
 
+            Rust_original::check_executable(&self.data, "evcxr", None)?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let _res = Command::new("bash")
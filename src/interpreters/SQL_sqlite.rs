@@ -0,0 +1,257 @@
+use crate::interpreters::import::*;
+use crate::table::Table;
+use rusqlite::types::ValueRef;
+use rusqlite::{Batch, Connection};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// in-memory scratch database, kept as long as sniprun (ie: the neovim session) is running
+static SCRATCH_DB: Mutex<Option<Connection>> = Mutex::new(None);
+
+const MEMORY_DB: &str = ":memory:";
+
+#[derive(Clone)]
+#[allow(non_camel_case_types)]
+pub struct SQL_sqlite {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+    database: String,
+}
+
+impl ReplLikeInterpreter for SQL_sqlite {}
+
+impl SQL_sqlite {
+    /// the 'database' option if set, else the first .db/.sqlite file next
+    /// to the buffer, else the in-memory scratch database
+    fn find_database(data: &DataHolder) -> String {
+        let buffer_dir = Path::new(&data.filepath)
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+
+        if let Some(database) = SQL_sqlite::get_interpreter_option(data, "database") {
+            if let Some(database_str) = database.as_str() {
+                if database_str == MEMORY_DB || Path::new(database_str).is_absolute() {
+                    return database_str.to_string();
+                }
+                return buffer_dir.join(database_str).to_string_lossy().to_string();
+            }
+        }
+
+        let mut candidates: Vec<String> = std::fs::read_dir(&buffer_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| {
+                        p.is_file()
+                            && matches!(
+                                p.extension().and_then(|ext| ext.to_str()),
+                                Some("db") | Some("sqlite") | Some("sqlite3")
+                            )
+                    })
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        candidates.sort();
+        candidates
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| String::from(MEMORY_DB))
+    }
+
+    fn format_value(value: ValueRef) -> String {
+        match value {
+            ValueRef::Null => String::from("NULL"),
+            ValueRef::Integer(i) => i.to_string(),
+            ValueRef::Real(f) => f.to_string(),
+            ValueRef::Text(t) => String::from_utf8_lossy(t).to_string(),
+            ValueRef::Blob(b) => format!("<blob: {} bytes>", b.len()),
+        }
+    }
+
+    /// run every statement of the code, returning the rows as table blocks
+    /// rows changed by INSERT/UPDATE/DELETE statements since the connection was opened
+    fn total_changes(conn: &Connection) -> i32 {
+        unsafe { rusqlite::ffi::sqlite3_total_changes(conn.handle()) }
+    }
+
+    fn run_statements(conn: &Connection, code: &str) -> Result<String, rusqlite::Error> {
+        let mut output = String::new();
+        let mut batch = Batch::new(conn, code);
+        while let Some(mut statement) = batch.next()? {
+            if statement.column_count() == 0 {
+                // the count of raw_execute() is the one of the last INSERT/UPDATE/DELETE,
+                // even after a statement that changed no row (ex: CREATE TABLE)
+                let total_before = SQL_sqlite::total_changes(conn);
+                let changed = statement.raw_execute()?;
+                if SQL_sqlite::total_changes(conn) != total_before {
+                    output.push_str(&format!("{} row(s) affected\n", changed));
+                }
+                continue;
+            }
+            let header = statement
+                .column_names()
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            let column_count = statement.column_count();
+            let mut rows = vec![];
+            let mut query = statement.raw_query();
+            while let Some(row) = query.next()? {
+                let mut cells = vec![];
+                for i in 0..column_count {
+                    cells.push(SQL_sqlite::format_value(row.get_ref(i)?));
+                }
+                rows.push(cells);
+            }
+            output.push_str(&Table::new(header, rows).to_output());
+        }
+        Ok(output)
+    }
+}
+
+impl Interpreter for SQL_sqlite {
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<SQL_sqlite> {
+        let database = SQL_sqlite::find_database(&data);
+        Box::new(SQL_sqlite {
+            data,
+            support_level,
+            code: String::from(""),
+            database,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![
+            String::from("SQL"),
+            String::from("sql"),
+            String::from("sqlite"),
+        ]
+    }
+
+    fn get_name() -> String {
+        String::from("SQL_sqlite")
+    }
+
+    fn get_current_level(&self) -> SupportLevel {
+        self.support_level
+    }
+    fn set_current_level(&mut self, level: SupportLevel) {
+        self.support_level = level;
+    }
+
+    fn get_data(&self) -> DataHolder {
+        self.data.clone()
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        //add code from data to self.code
+        if !self
+            .data
+            .current_bloc
+            .replace(&[' ', '\t', '\n', '\r'][..], "")
+            .is_empty()
+            && self.support_level >= SupportLevel::Bloc
+        {
+            self.code.clone_from(&self.data.current_bloc);
+        } else if !self.data.current_line.replace(' ', "").is_empty()
+            && self.support_level >= SupportLevel::Line
+        {
+            self.code.clone_from(&self.data.current_line);
+        } else {
+            self.code = String::from("");
+        }
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        // statements are run in-process, nothing to build
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<String, SniprunError> {
+        info!("running sqlite statements on {}", self.database);
+        let result = if self.database == MEMORY_DB {
+            let mut scratch_db = SCRATCH_DB.lock().unwrap();
+            if scratch_db.is_none() {
+                *scratch_db = Some(Connection::open_in_memory().map_err(|e| {
+                    SniprunError::CustomError(format!("Could not open in-memory database: {}", e))
+                })?);
+            }
+            SQL_sqlite::run_statements(scratch_db.as_ref().unwrap(), &self.code)
+        } else {
+            let conn = Connection::open(&self.database).map_err(|e| {
+                SniprunError::CustomError(format!(
+                    "Could not open database {}: {}",
+                    self.database, e
+                ))
+            })?;
+            SQL_sqlite::run_statements(&conn, &self.code)
+        };
+        result.map_err(|e| SniprunError::RuntimeError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test_sql_sqlite {
+    use super::*;
+    use crate::table::extract_tables;
+    use serial_test::serial;
+
+    #[test]
+    #[serial(sqlite_scratch)]
+    fn scratch_db_persists() {
+        let mut data = DataHolder::new();
+        data.current_bloc = String::from(
+            "create table if not exists t (id integer, name text);\ninsert into t values (1, 'a'), (2, NULL);",
+        );
+        let mut interpreter = SQL_sqlite::new(data);
+        let res = interpreter.run_at_level(SupportLevel::Bloc);
+        assert!(res.unwrap().contains("2 row(s) affected"));
+
+        // statements that change no row don't repeat the previous count
+        let mut data = DataHolder::new();
+        data.current_bloc = String::from(
+            "insert into t values (3, 'c');\ncreate table if not exists u (id integer);\ndrop table u;",
+        );
+        let mut interpreter = SQL_sqlite::new(data);
+        let res = interpreter.run_at_level(SupportLevel::Bloc);
+        assert_eq!(res.unwrap(), "1 row(s) affected\n");
+        let mut data = DataHolder::new();
+        data.current_bloc = String::from("delete from t where id = 3;");
+        let mut interpreter = SQL_sqlite::new(data);
+        assert_eq!(
+            interpreter.run_at_level(SupportLevel::Bloc).unwrap(),
+            "1 row(s) affected\n"
+        );
+
+        let mut data = DataHolder::new();
+        data.current_bloc = String::from("select id, name from t order by id;");
+        let mut interpreter = SQL_sqlite::new(data);
+        let res = interpreter.run_at_level(SupportLevel::Bloc).unwrap();
+        let tables = extract_tables(&res);
+        assert_eq!(tables[0].header, vec!["id", "name"]);
+        assert_eq!(tables[0].rows[..2], [vec!["1", "a"], vec!["2", "NULL"]]);
+    }
+
+    #[test]
+    #[serial(sqlite_scratch)]
+    fn syntax_error() {
+        let mut data = DataHolder::new();
+        data.current_bloc = String::from("selec 1;");
+        let mut interpreter = SQL_sqlite::new(data);
+        let res = interpreter.run_at_level(SupportLevel::Bloc);
+        assert!(matches!(res, Err(SniprunError::RuntimeError(_))));
+    }
+}
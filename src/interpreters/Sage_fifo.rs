@@ -50,7 +50,7 @@ impl Sage_fifo {
             if let Ok(mut file) = std::fs::File::open(&err_path) {
                 info!("errfile exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 if res.is_ok() {
                     info!("errfile could be read : {:?}", err_contents);
                     // info!("file : {:?}", contents);
@@ -73,7 +73,7 @@ impl Sage_fifo {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -374,6 +374,7 @@ impl ReplLikeInterpreter for Sage_fifo {
                 "launching kernel : {:?} on {:?}",
                 init_repl_cmd, &self.cache_dir
             );
+            Sage_fifo::check_executable(&self.data, &self.interpreter, Some("interpreter"))?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let nodotstage_arg = if self.user_sage_config {
@@ -114,19 +114,21 @@ impl Interpreter for Scala_original {
 
         let compiler = Scala_original::get_compiler_or(&self.data, "scalac");
         //compile it (to the bin_path that arleady points to the rigth path)
-        let output = Command::new(compiler.split_whitespace().next().unwrap())
-            .args(compiler.split_whitespace().skip(1))
-            .arg("-d")
-            .arg(&self.language_work_dir)
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+        let output = Scala_original::run_command(
+            &self.data,
+            Command::new(compiler.split_whitespace().next().unwrap())
+                .args(compiler.split_whitespace().skip(1))
+                .arg("-d")
+                .arg(&self.language_work_dir)
+                .arg(&self.main_file_path),
+            Some("compiler"),
+        )?;
 
         // if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            Err(SniprunError::CompilationError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::CompilationError(decode_output(
+                &output.stderr,
+            )))
         } else {
             info!("scala compiled successfully");
             Ok(())
@@ -136,28 +138,27 @@ impl Interpreter for Scala_original {
     fn execute(&mut self) -> Result<String, SniprunError> {
         //run th binary and get the std output (or stderr)
         let interpreter = Scala_original::get_interpreter_or(&self.data, "scala");
-        let output = Command::new(interpreter)
-            .arg("Main")
-            .current_dir(&self.language_work_dir)
-            .output()
-            .expect("Unable to start process");
+        let output = Scala_original::run_command(
+            &self.data,
+            Command::new(interpreter)
+                .arg("Main")
+                .current_dir(&self.language_work_dir),
+            Some("interpreter"),
+        )?;
 
         if output.status.success() {
             //return stdout
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Scala_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .next()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ))
         } else {
-            Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ))
+            Err(SniprunError::RuntimeError(decode_output(&output.stderr)))
         }
     }
 }
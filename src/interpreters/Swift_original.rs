@@ -57,7 +57,7 @@ impl Swift_original {
             if let Ok(mut file) = err_file_empty {
                 info!("errfile exists");
                 err_contents.clear();
-                let res = file.read_to_string(&mut err_contents);
+                let res = read_output(&mut file, &mut err_contents);
                 info!("errfile read staus :{:?}", res);
                 if res.is_ok() {
                     info!("errfile could be read : {:?}", err_contents);
@@ -86,7 +86,7 @@ impl Swift_original {
             if let Ok(mut file) = std::fs::File::open(&out_path) {
                 info!("file exists");
                 out_contents.clear();
-                let res = file.read_to_string(&mut out_contents);
+                let res = read_output(&mut file, &mut out_contents);
                 if res.is_ok() {
                     info!("file could be read : {:?}", out_contents);
                     // info!("file : {:?}", contents);
@@ -278,13 +278,13 @@ impl Interpreter for Swift_original {
             format!("{:?}", cmd).replace('\"', "")
         );
 
-        let output = cmd.output().expect("Unable to start process");
+        let output = Swift_original::run_command(&self.data, cmd, Some("compiler"))?;
         //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            let error_message = String::from_utf8(output.stderr).unwrap();
+            let error_message = decode_output(&output.stderr);
             Err(SniprunError::CompilationError(error_message))
         } else {
-            let compiler_output = String::from_utf8(output.stdout).unwrap();
+            let compiler_output = decode_output(&output.stdout);
             info!("compiler output:\n{}\n", compiler_output);
             Ok(())
         }
@@ -296,20 +296,17 @@ impl Interpreter for Swift_original {
         info!("cmd: {:?}", &cmd);
         let output = cmd.output().expect("Unable to start process");
         if output.status.success() {
-            Ok(String::from_utf8(output.stdout).unwrap())
+            Ok(decode_output(&output.stdout))
         } else if Swift_original::error_truncate(&self.get_data()) == ErrTruncate::Short {
             return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr.clone())
-                    .unwrap()
+                decode_output(&output.stderr)
                     .lines()
                     .last()
-                    .unwrap_or(&String::from_utf8(output.stderr).unwrap())
+                    .unwrap_or(&decode_output(&output.stderr))
                     .to_owned(),
             ));
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::RuntimeError(decode_output(&output.stderr)));
         }
     }
 }
@@ -358,6 +355,7 @@ impl ReplLikeInterpreter for Swift_original {
             ])
             .args(self.interpreter.split_whitespace().skip(1));
             info!("init repl cmd = {:?}", cmd);
+            Swift_original::check_executable(&self.data, &self.interpreter, Some("interpreter"))?;
             match daemon() {
                 Ok(Fork::Child) => {
                     let mut cmd = Command::new("bash");
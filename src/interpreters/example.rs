@@ -125,7 +125,6 @@ impl Interpreter for Language_subname {
         write(&self.main_file_path, &self.code)
             .expect("Unable to write to file for language_subname");
 
-
         //fetch the option from the configuration
         //  interpreter_options = {
         //   example_original = {
@@ -141,8 +140,6 @@ impl Interpreter for Language_subname {
             }
         }
 
-
-
         let compiler = Language_subname::get_compiler_or(&self.data, "compiler");
         //compile it (to the bin_path that already points to the rigth path)
         let output = Command::new(compiler)
@@ -171,12 +168,10 @@ impl Interpreter for Language_subname {
 
         if output.status.success() {
             //return stdout
-            return Ok(String::from_utf8(output.stdout).unwrap());
+            return Ok(decode_output(&output.stdout));
         } else {
             // return stderr
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            return Err(SniprunError::RuntimeError(decode_output(&output.stderr)));
         }
     }
 }
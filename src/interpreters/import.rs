@@ -1,3 +1,4 @@
+pub use crate::decode::{decode_output, read_output};
 pub use crate::error::SniprunError;
 pub use crate::interpreter::{
     ErrTruncate, Interpreter, InterpreterUtils, ReplLikeInterpreter, SupportLevel,
@@ -1,8 +1,10 @@
+use crate::decode::decode_output;
 use crate::interpreter::InterpreterUtils;
 use crate::*;
 use error::SniprunError;
 use interpreter::{Interpreter, SupportLevel};
 use interpreters::Generic::Generic;
+use interpreters::Jupyter_generic::Jupyter_generic;
 use std::any::TypeId;
 use std::io::prelude::*;
 use std::process::Command;
@@ -23,6 +25,8 @@ impl Launcher {
     {
         let supported_languages = if TypeId::of::<T>() == TypeId::of::<Generic>() {
             Generic::get_configured_filetypes(data)
+        } else if TypeId::of::<T>() == TypeId::of::<Jupyter_generic>() {
+            Jupyter_generic::get_configured_filetypes(data)
         } else {
             T::get_supported_languages()
         };
@@ -98,6 +102,17 @@ impl Launcher {
         Some((name_best_interpreter, max_level_support))
     }
 
+    /// names of the interpreters able to run the current filetype
+    pub fn interpreters_for_filetype(&self) -> Vec<String> {
+        let mut names = vec![];
+        iter_types! {
+            if Launcher::match_filetype::<Current>(self.data.filetype.clone(), &self.data){
+                names.push(Current::get_name());
+            }
+        }
+        names
+    }
+
     pub fn info(&self) -> std::io::Result<String> {
         let mut v: Vec<String> = vec![];
         let filename = self.data.sniprun_root_dir.clone() + "/ressources/asciiart.txt";
@@ -117,7 +132,7 @@ impl Launcher {
         if let Ok(res) = get_version.output() {
             info!("gitscript result: {:?}", res);
             if res.status.success() {
-                let online_version = String::from_utf8(res.stdout).unwrap();
+                let online_version = decode_output(&res.stdout);
                 info!("online version available: {}", &online_version);
                 v.push(online_version);
             } else {
@@ -216,4 +231,36 @@ mod test_launcher {
         let launcher = Launcher::new(data);
         let _res = launcher.info().unwrap();
     }
+    #[test]
+    fn missing_executable() {
+        use interpreters::Bash_original::Bash_original;
+        let mut data = DataHolder::new();
+        data.filetype = String::from("sh");
+        let res = Bash_original::run_command(
+            &data,
+            &mut Command::new("sniprun_missing_executable"),
+            Some("interpreter"),
+        );
+        match res {
+            Err(SniprunError::MissingExecutable {
+                executable,
+                option,
+                interpreter,
+                alternatives,
+            }) => {
+                assert_eq!(executable, "sniprun_missing_executable");
+                assert_eq!(option.as_deref(), Some("interpreter"));
+                assert_eq!(interpreter, "Bash_original");
+                assert!(!alternatives.contains(&interpreter));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // for the REPLs, launched in the background
+        assert!(Bash_original::check_executable(&data, "bash -i", None).is_ok());
+        assert!(matches!(
+            Bash_original::check_executable(&data, "sniprun_missing_executable -i", None),
+            Err(SniprunError::MissingExecutable { option: None, .. })
+        ));
+    }
 }
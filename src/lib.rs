@@ -3,21 +3,29 @@
 //! Sniprun is a neovim plugin that run parts of code.
 
 use dirs::cache_dir;
-pub use display::{display, display_floating_window, DisplayFilter::*, DisplayType};
+pub use display::{display, display_floating_window, DisplayFilter::*, DisplayRule, DisplayType};
 use log::{info, LevelFilter};
 use neovim_lib::{Neovim, NeovimApi, Session, Value};
 use simple_logging::log_to_file;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
+pub mod ansi;
 pub mod daemonizer;
+pub mod decode;
+pub mod diff;
 pub mod display;
 pub mod error;
+pub mod history;
 pub mod input;
 pub mod interpreter;
 pub mod interpreters;
 pub mod launcher;
+pub mod quickfix;
+pub mod table;
 
 ///This struct holds (with ownership) the data Sniprun and neovim
 ///give to the interpreter.
@@ -69,9 +77,20 @@ pub struct DataHolder {
     /// different way of displaying results
     pub display_type: Vec<DisplayType>,
     pub display_no_output: Vec<DisplayType>,
+    /// per filetype/interpreter/result overrides of `display_type`
+    pub display_rules: Vec<DisplayRule>,
 
     pub cli_args: Vec<String>,
 
+    /// identifies the current run (increasing, from 1)
+    pub run_id: usize,
+    /// when the current run started
+    pub run_start: Option<Instant>,
+    /// how long the interpreter took, once the run is over
+    pub run_duration: Option<Duration>,
+    /// number of the buffer the current run was started from
+    pub bufnr: i64,
+
     pub nvim_pid: usize,
 }
 
@@ -121,7 +140,12 @@ impl Default for DataHolder {
             return_message_type: ReturnMessageType::Multiline,
             display_type: vec![DisplayType::Classic(Both)],
             display_no_output: vec![DisplayType::Classic(Both)],
+            display_rules: vec![],
             cli_args: vec![],
+            run_id: 0,
+            run_start: None,
+            run_duration: None,
+            bufnr: 0,
             nvim_pid: 0,
         }
     }
@@ -139,6 +163,24 @@ impl DataHolder {
         std::fs::create_dir_all(&work_dir_path).unwrap();
     }
 
+    /// give an id to a new run, and record when and from which buffer it started
+    pub fn start_run(&mut self) {
+        static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        self.run_id = RUN_COUNTER.fetch_add(1, Ordering::SeqCst) + 1;
+        self.run_start = Some(Instant::now());
+        if let Some(nvim_instance) = self.nvim_instance.clone() {
+            let mut nvim_instance = nvim_instance.lock().unwrap();
+            if let Ok(buffer) = nvim_instance.get_current_buf() {
+                self.bufnr = buffer.get_number(&mut nvim_instance).unwrap_or(0);
+            }
+        }
+    }
+
+    /// record how long the run took, before its result gets displayed
+    pub fn end_run(&mut self) {
+        self.run_duration = self.run_start.map(|start| start.elapsed());
+    }
+
     pub fn modify_for_range(&mut self, range: (usize, usize)) {
         self.range = [range.0 as i64, range.1 as i64];
         let nvim_instance = self.nvim_instance.clone().unwrap();
@@ -169,6 +211,9 @@ enum Messages {
     ClearReplMemory,
     Info,
     Ping,
+    HttpCookies,
+    HttpCurl,
+    History,
     Unknown(String),
 }
 
@@ -180,6 +225,9 @@ impl From<String> for Messages {
             "clearrepl" => Messages::ClearReplMemory,
             "ping" => Messages::Ping,
             "info" => Messages::Info,
+            "httpcookies" => Messages::HttpCookies,
+            "httpcurl" => Messages::HttpCurl,
+            "history" => Messages::History,
             _ => Messages::Unknown(event),
         }
     }
@@ -251,7 +299,14 @@ impl EventHandler {
             }
         }
 
+        if let Some(root) = self
+            .index_from_name("project_root", config)
+            .and_then(|i| config[i].1.as_str())
         {
+            // the same directory as the completions of the lua side
+            self.data.projectroot = String::from(root);
+            info!("[FILLDATA] got project root: {}", self.data.projectroot);
+        } else {
             //get neovim's current directory
             let nvim_cwd = self
                 .nvim
@@ -369,6 +424,19 @@ impl EventHandler {
                 info!("[FILLDATA] got display types");
             }
         }
+        {
+            if let Some(i) = self.index_from_name("display_rules", config) {
+                self.data.display_rules = config[i]
+                    .1
+                    .as_array()
+                    .map(|rules| rules.iter().filter_map(DisplayRule::from_value).collect())
+                    .unwrap_or_default();
+                info!(
+                    "[FILLDATA] got display rules: {:?}",
+                    self.data.display_rules
+                );
+            }
+        }
         {
             if let Some(i) = self.index_from_name("show_no_output", config) {
                 self.data.display_no_output = config[i]
@@ -496,6 +564,7 @@ pub fn start() {
                     info!("[RUN] spawned thread");
                     event_handler2.fill_data(&values);
                     event_handler2.override_data(values);
+                    event_handler2.data.start_run();
                     info!("[RUN] filled dataholder");
 
                     //run the launcher (that selects, init and run an interpreter)
@@ -503,6 +572,7 @@ pub fn start() {
                     info!("[RUN] created launcher");
                     let result = launcher.select_and_run();
                     info!("[RUN] Interpreter return a result");
+                    event_handler2.data.end_run();
 
                     if let Err(error::SniprunError::ReRunRanges(vec)) = result {
                         // sniprun separated into multiple ranges from
@@ -514,15 +584,19 @@ pub fn start() {
                                 .clone()
                                 .expect("Nvim instance not available");
                             data.modify_for_range(range);
+                            data.start_run();
                             let launcher = launcher::Launcher::new(data.clone());
                             info!("[RUN] created launcher");
                             let result = launcher.select_and_run();
                             info!("[RUN] Interpreter return a result");
+                            data.end_run();
+                            history::record(&result, &data);
                             data.range[1] += 1; // display on end of code bloc
                             display(result, nvim, &data);
                         }
                     } else {
                         // normal, unique result
+                        history::record(&result, &event_handler2.data);
                         display(result, event_handler2.nvim, &event_handler2.data);
                     }
 
@@ -566,6 +640,67 @@ pub fn start() {
                 }
             }
 
+            Messages::HttpCookies => {
+                info!("[MAINLOOP] HttpCookies command received");
+                let mut event_handler2 = event_handler.clone();
+                event_handler2.fill_data(&values);
+                // 'clear', or empty to list the cookies
+                let action = values.get(3).and_then(|v| v.as_str()).unwrap_or("");
+                let cookies = interpreters::Http_original::Http_original::cookies(
+                    &event_handler2.data,
+                    action,
+                );
+                display_floating_window(
+                    &Ok(cookies),
+                    &event_handler2.nvim,
+                    &event_handler2.data,
+                    false,
+                    Both,
+                );
+            }
+
+            Messages::History => {
+                info!("[MAINLOOP] History command received");
+                let mut event_handler2 = event_handler.clone();
+                event_handler2.fill_data(&values);
+                // 'clear', or empty to pick a run
+                match values.get(3).and_then(|v| v.as_str()).unwrap_or("") {
+                    "clear" => {
+                        history::clear(&event_handler2.data);
+                        display_floating_window(
+                            &Ok(String::from("History cleared")),
+                            &event_handler2.nvim,
+                            &event_handler2.data,
+                            false,
+                            Both,
+                        );
+                    }
+                    _ => history::show(&event_handler2.data, &event_handler2.nvim),
+                }
+            }
+
+            Messages::HttpCurl => {
+                info!("[MAINLOOP] HttpCurl command received");
+                let mut event_handler2 = event_handler.clone();
+                event_handler2.fill_data(&values);
+                // 'to_curl [register]' or 'from_curl'
+                let action = values.get(3).and_then(|v| v.as_str()).unwrap_or("");
+                let result = interpreters::Http_original::Http_original::curl_command(
+                    &event_handler2.data,
+                    action,
+                );
+                // the converted request replaces the curl command in the buffer, only show errors
+                if result.is_err() || !action.starts_with("from_curl") {
+                    display_floating_window(
+                        &result,
+                        &event_handler2.nvim,
+                        &event_handler2.data,
+                        false,
+                        Both,
+                    );
+                }
+            }
+
             Messages::Unknown(event) => {
                 info!("[MAINLOOP] Unknown event received: {:?}", event);
             }
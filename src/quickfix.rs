@@ -0,0 +1,161 @@
+//! Error locations, for the Quickfix and Loclist displays
+//!
+//! `file:line:col: message` (and `file:line: message`) lines and Python traceback
+//! frames are extracted from the output. The files generated by the interpreters
+//! (in sniprun's work directory) are mapped back to the current buffer.
+
+use crate::DataHolder;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuickfixEntry {
+    /// None for the current buffer
+    pub filename: Option<String>,
+    pub lnum: i64,
+    pub col: i64,
+    pub text: String,
+}
+
+/// extract the error locations from an output
+pub fn parse_locations(output: &str, data: &DataHolder) -> Vec<QuickfixEntry> {
+    let output = strip_ansi_escapes::strip_str(output);
+    let location = Regex::new(r#"^([^\s:"'<>][^:"]*):(\d+):(?:(\d+):)?\s*(.*)$"#).unwrap();
+    let frame = Regex::new(r#"^\s*File "(.+)", line (\d+)"#).unwrap();
+    // the exception message closes the traceback
+    let exception = output
+        .lines()
+        .rfind(|l| !l.trim().is_empty() && !l.starts_with(char::is_whitespace))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let mut entries = vec![];
+    for line in output.lines() {
+        if let Some(caps) = frame.captures(line) {
+            entries.push(entry(&caps[1], &caps[2], None, &exception, data));
+        } else if let Some(caps) = location.captures(line) {
+            let col = caps.get(3).map(|c| c.as_str());
+            entries.push(entry(&caps[1], &caps[2], col, &caps[4], data));
+        }
+    }
+    entries
+}
+
+fn entry(
+    file: &str,
+    line: &str,
+    col: Option<&str>,
+    text: &str,
+    data: &DataHolder,
+) -> QuickfixEntry {
+    let lnum = line.parse().unwrap_or(1);
+    let col = col.and_then(|c| c.parse().ok()).unwrap_or(0);
+    let text = text.to_string();
+    let resolved = resolve_file(file, data);
+    let outside_work_dir = |p: &Path| !p.starts_with(&data.work_dir);
+    match resolved {
+        Some(path) if outside_work_dir(&path) => QuickfixEntry {
+            filename: Some(path.to_string_lossy().into_owned()),
+            lnum,
+            col,
+            text,
+        },
+        // still worth listing, even if it can't be opened
+        None if Path::new(file).is_absolute() && outside_work_dir(Path::new(file)) => {
+            QuickfixEntry {
+                filename: Some(file.to_string()),
+                lnum,
+                col,
+                text,
+            }
+        }
+        generated => QuickfixEntry {
+            filename: None,
+            lnum: buffer_line(generated.as_deref(), lnum, data),
+            col,
+            text,
+        },
+    }
+}
+
+/// the existing file designated by 'file', if any
+fn resolve_file(file: &str, data: &DataHolder) -> Option<PathBuf> {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        return path.exists().then(|| path.to_path_buf());
+    }
+    let in_project = Path::new(&data.projectroot).join(path);
+    if in_project.exists() {
+        return Some(in_project);
+    }
+    // relative to the directory an interpreter compiled or ran it from
+    std::fs::read_dir(&data.work_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().join(path))
+        .filter(|p| p.exists())
+        .max_by_key(|p| p.metadata().and_then(|m| m.modified()).ok())
+}
+
+/// the line of the current buffer matching the line 'lnum' of a generated file
+fn buffer_line(generated: Option<&Path>, lnum: i64, data: &DataHolder) -> i64 {
+    let code_line = generated
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| {
+            let line = content.lines().nth((lnum - 1).max(0) as usize)?.trim();
+            (!line.is_empty()).then(|| line.to_string())
+        });
+    let code = if data.current_bloc.trim().is_empty() {
+        &data.current_line
+    } else {
+        &data.current_bloc
+    };
+    code_line
+        .and_then(|code_line| code.lines().position(|l| l.trim() == code_line))
+        .map_or(data.range[0], |i| data.range[0] + i as i64)
+}
+
+#[cfg(test)]
+mod test_quickfix {
+    use super::*;
+
+    #[test]
+    fn locations() {
+        let work_dir = std::env::temp_dir().join("sniprun_test_quickfix");
+        std::fs::create_dir_all(work_dir.join("cpp_original")).unwrap();
+        let main = work_dir.join("cpp_original/main.cpp");
+        std::fs::write(
+            &main,
+            "#include <iostream>\nint main() {\nint x = y;\nreturn 0;}",
+        )
+        .unwrap();
+        let data = DataHolder {
+            work_dir: work_dir.to_string_lossy().into_owned(),
+            current_bloc: "int a = 1;\nint x = y;".to_string(),
+            range: [10, 11],
+            ..Default::default()
+        };
+
+        let output = format!(
+            "{}:3:9: error: 'y' was not declared in this scope\n    3 | int x = y;\n\
+             /usr/include/foo.h:1:2: note: declared here\nhttp://example.com:80 is up\n",
+            main.display()
+        );
+        let entries = parse_locations(&output, &data);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].filename, None);
+        assert_eq!((entries[0].lnum, entries[0].col), (11, 9));
+        assert_eq!(entries[0].text, "error: 'y' was not declared in this scope");
+        assert_eq!(entries[1].filename, Some("/usr/include/foo.h".to_string()));
+        assert_eq!(entries[1].lnum, 1);
+
+        let traceback = "Traceback (most recent call last):\n  File \"/usr/lib/python3/json/__init__.py\", line 4, in loads\n    return x\n  File \"<string>\", line 2, in <module>\nNameError: name 'y' is not defined";
+        let entries = parse_locations(traceback, &data);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].lnum, 4);
+        assert_eq!(entries[1].filename, None);
+        assert_eq!(entries[1].text, "NameError: name 'y' is not defined");
+        assert_eq!(entries[1].lnum, 10);
+    }
+}
@@ -0,0 +1,250 @@
+//! Structured (tabular) results
+//!
+//! Interpreters can embed tables in their output, as TSV rows enclosed between
+//! TABLE_START and TABLE_END lines. The displays then render them as aligned tables.
+
+/// line opening a table block in an interpreter's output
+pub const TABLE_START: &str = "sniprun_table_start";
+/// line closing a table block in an interpreter's output
+pub const TABLE_END: &str = "sniprun_table_end";
+
+/// line drawn under the header of a rendered table, also used by the
+/// floating window to find (and highlight) the header line
+const SEPARATOR_LINE: char = '─';
+const SEPARATOR_CROSS: char = '┼';
+const COLUMN_SEPARATOR: &str = " │ ";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        Table { header, rows }
+    }
+
+    /// parse TSV lines, the first one being the header
+    pub fn from_tsv(tsv: &str) -> Self {
+        let mut lines = tsv
+            .lines()
+            .map(|l| l.split('\t').map(|s| s.to_string()).collect());
+        let header = lines.next().unwrap_or_default();
+        Table {
+            header,
+            rows: lines.collect(),
+        }
+    }
+
+    /// parse CSV (RFC 4180, with quoted fields) text, the first record being the header
+    pub fn from_csv(csv: &str) -> Self {
+        let mut records: Vec<Vec<String>> = vec![];
+        let mut record = vec![];
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = csv.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, in_quotes) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', true) => in_quotes = false,
+                ('"', false) if field.is_empty() => in_quotes = true,
+                (',', false) => record.push(std::mem::take(&mut field)),
+                ('\r', false) => {}
+                ('\n', false) => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            records.push(record);
+        }
+        let mut records = records.into_iter();
+        Table {
+            header: records.next().unwrap_or_default(),
+            rows: records.collect(),
+        }
+    }
+
+    /// the table as a block to embed in an interpreter's output
+    pub fn to_output(&self) -> String {
+        let clean = |cells: &Vec<String>| {
+            cells
+                .iter()
+                .map(|c| c.replace(['\t', '\n'], " "))
+                .collect::<Vec<_>>()
+                .join("\t")
+        };
+        let mut block = vec![TABLE_START.to_string(), clean(&self.header)];
+        block.extend(self.rows.iter().map(clean));
+        block.push(TABLE_END.to_string());
+        block.join("\n") + "\n"
+    }
+
+    pub fn to_csv(&self) -> String {
+        let quote = |cells: &Vec<String>| {
+            cells
+                .iter()
+                .map(|c| {
+                    if c.contains([',', '"', '\n']) {
+                        format!("\"{}\"", c.replace('"', "\"\""))
+                    } else {
+                        c.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        std::iter::once(&self.header)
+            .chain(self.rows.iter())
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// render as aligned columns, each cell being truncated to max_width characters
+    pub fn render(&self, max_width: usize) -> String {
+        let truncate = |cell: &str| -> String {
+            if max_width > 0 && cell.chars().count() > max_width {
+                cell.chars()
+                    .take(max_width.saturating_sub(1))
+                    .collect::<String>()
+                    + "…"
+            } else {
+                cell.to_string()
+            }
+        };
+        let n_columns = std::iter::once(&self.header)
+            .chain(self.rows.iter())
+            .map(|r| r.len())
+            .max()
+            .unwrap_or(0);
+        let cells: Vec<Vec<String>> = std::iter::once(&self.header)
+            .chain(self.rows.iter())
+            .map(|r| {
+                (0..n_columns)
+                    .map(|i| truncate(r.get(i).map(|s| s.as_str()).unwrap_or("")))
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = (0..n_columns)
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|r| r[i].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let render_row = |row: &Vec<String>| {
+            row.iter()
+                .zip(widths.iter())
+                .map(|(cell, w)| format!("{}{}", cell, " ".repeat(w - cell.chars().count())))
+                .collect::<Vec<_>>()
+                .join(COLUMN_SEPARATOR)
+                .trim_end()
+                .to_string()
+        };
+        let separator = widths
+            .iter()
+            .map(|w| SEPARATOR_LINE.to_string().repeat(*w))
+            .collect::<Vec<_>>()
+            .join(&format!(
+                "{}{}{}",
+                SEPARATOR_LINE, SEPARATOR_CROSS, SEPARATOR_LINE
+            ));
+
+        let mut lines = vec![render_row(&cells[0]), separator];
+        lines.extend(cells[1..].iter().map(render_row));
+        lines.join("\n")
+    }
+}
+
+/// returns the tables embedded in a message
+pub fn extract_tables(message: &str) -> Vec<Table> {
+    let mut tables = vec![];
+    let mut current: Option<Vec<&str>> = None;
+    for line in message.lines() {
+        match (line.trim_end(), current.as_mut()) {
+            (TABLE_START, _) => current = Some(vec![]),
+            (TABLE_END, Some(rows)) => {
+                tables.push(Table::from_tsv(&rows.join("\n")));
+                current = None;
+            }
+            (_, Some(rows)) => rows.push(line),
+            _ => {}
+        }
+    }
+    tables
+}
+
+/// replace the tables embedded in a message by their aligned rendering
+pub fn render_tables(message: &str, max_width: usize) -> String {
+    if !message.contains(TABLE_START) {
+        return message.to_string();
+    }
+    let mut rendered = vec![];
+    let mut current: Option<Vec<&str>> = None;
+    for line in message.lines() {
+        match (line.trim_end(), current.as_mut()) {
+            (TABLE_START, _) => current = Some(vec![]),
+            (TABLE_END, Some(rows)) => {
+                rendered.push(Table::from_tsv(&rows.join("\n")).render(max_width));
+                current = None;
+            }
+            (_, Some(rows)) => rows.push(line),
+            _ => rendered.push(line.to_string()),
+        }
+    }
+    // unterminated table, leave it as-is
+    if let Some(rows) = current {
+        rendered.push(TABLE_START.to_string());
+        rendered.extend(rows.iter().map(|s| s.to_string()));
+    }
+    let trailing_newline = if message.ends_with('\n') { "\n" } else { "" };
+    rendered.join("\n") + trailing_newline
+}
+
+#[cfg(test)]
+mod test_table {
+    use super::*;
+
+    #[test]
+    fn csv_roundtrip() {
+        let table = Table::from_csv("id,name\n1,\"Doe, John\"\n2,\"say \"\"hi\"\"\"\n");
+        assert_eq!(table.header, vec!["id", "name"]);
+        assert_eq!(table.rows[0], vec!["1", "Doe, John"]);
+        assert_eq!(table.rows[1], vec!["2", "say \"hi\""]);
+        assert_eq!(Table::from_csv(&table.to_csv()), table);
+    }
+
+    #[test]
+    fn render_aligned_and_truncated() {
+        let table = Table::from_tsv("id\tname\n1\tAlice\n22\tBartholomew");
+        assert_eq!(
+            table.render(6),
+            "id │ name\n───┼───────\n1  │ Alice\n22 │ Barth…"
+        );
+    }
+
+    #[test]
+    fn render_embedded_tables() {
+        let table = Table::new(
+            vec!["a".into(), "b".into()],
+            vec![vec!["1".into(), "2".into()]],
+        );
+        let message = String::from("before\n") + &table.to_output() + "after\n";
+        assert_eq!(extract_tables(&message), vec![table]);
+        assert_eq!(
+            render_tables(&message, 0),
+            "before\na │ b\n──┼──\n1 │ 2\nafter\n"
+        );
+    }
+}